@@ -0,0 +1,87 @@
+//! Compares fuzzy search through the persisted index with a scan of every todo.
+//!
+//! Run with `cargo bench --bench search`; set `TODO_BENCH_SIZE` to change the
+//! number of todos (default 50000).
+
+use std::env;
+use std::fs;
+use std::time::{Duration, Instant};
+
+use todo_list::{Search, Status, Todo, TodoList};
+
+const WORDS: &[&str] = &[
+    "call", "plumber", "buy", "groceries", "project", "management", "review", "invoice", "dentist", "renew",
+    "passport", "garden", "fence", "paint", "budget", "quarterly", "report", "backup", "server", "migrate",
+    "database", "birthday", "present", "library", "books", "return", "insurance", "claim", "tax", "return",
+    "meeting", "notes", "schedule", "flight", "hotel", "booking", "laundry", "kitchen", "repair", "bicycle",
+];
+const TAGS: &[&str] = &["work", "home", "errands", "finance", "health", "travel"];
+const QUERIES: &[&str] = &["plumber", "groceries", "projetc", "managing", "quarterly report", "nonexistent"];
+
+fn main() {
+    let size: usize = env::var("TODO_BENCH_SIZE").ok().and_then(|size| size.parse().ok()).unwrap_or(50_000);
+    let dir = env::temp_dir().join(format!("todo-bench-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("todos.json");
+
+    let todos = generate(size);
+    let store = serde_json::json!({ "schema_version": todo_list::schema::CURRENT_VERSION, "next_id": size + 1, "todos": todos });
+    fs::write(&path, serde_json::to_string(&store).unwrap()).unwrap();
+
+    let started = Instant::now();
+    let list = TodoList::open(&path).unwrap();
+    println!("{} todos, index built and saved in {:?}", size, started.elapsed());
+    let started = Instant::now();
+    let list = drop_and_reopen(list, &path);
+    println!("reopened with the saved index in {:?}\n", started.elapsed());
+
+    println!("{:<20} {:>8} {:>14} {:>14}", "query", "hits", "scan", "index");
+    for query in QUERIES {
+        let search = Search::fuzzy(query);
+        let (scan, scanned) = time(|| search.run(list.todos()).len());
+        let (index, indexed) = time(|| list.find(&search).len());
+        assert_eq!(scanned, indexed, "the index must find what the scan finds");
+        println!("{:<20} {:>8} {:>14?} {:>14?}", query, scanned, scan, index);
+    }
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+fn drop_and_reopen(list: TodoList, path: &std::path::Path) -> TodoList {
+    drop(list);
+    TodoList::open(path).unwrap()
+}
+
+/// Mean time per run over a handful of runs, with the last result.
+fn time<T>(mut run: impl FnMut() -> T) -> (Duration, T) {
+    const RUNS: u32 = 5;
+    let started = Instant::now();
+    let mut result = run();
+    for _ in 1..RUNS {
+        result = run();
+    }
+    (started.elapsed() / RUNS, result)
+}
+
+/// Titles of two to five words, mostly completed like an old archive.
+fn generate(size: usize) -> Vec<Todo> {
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = move |bound: usize| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        (seed % bound as u64) as usize
+    };
+    (1..=size)
+        .map(|id| {
+            let length = 2 + next(4);
+            let title: Vec<&str> = (0..length).map(|_| WORDS[next(WORDS.len())]).collect();
+            let mut todo = Todo::new(title.join(" "), None, None, vec![TAGS[next(TAGS.len())].to_string()]).unwrap();
+            todo.id = id;
+            if next(10) > 0 {
+                todo.status = Status::Done;
+            }
+            todo
+        })
+        .collect()
+}
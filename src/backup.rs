@@ -0,0 +1,147 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{Local, NaiveDateTime, TimeZone};
+
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%.6f";
+
+/// Number of backups kept next to the data file unless configured otherwise.
+pub const DEFAULT_BACKUP_LIMIT: usize = 5;
+
+/// A timestamped copy of the data file taken before it was overwritten.
+#[derive(Debug, Clone)]
+pub struct Backup {
+    pub path: PathBuf,
+    pub created_at: chrono::DateTime<Local>,
+}
+
+/// Writes `content` to a temporary file beside `path`, syncs it and renames it
+/// over `path`, so readers only ever see the old or the new contents.
+pub(crate) fn write_atomic(path: &Path, content: &[u8]) -> io::Result<()> {
+    let dir = parent_dir(path);
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = dir.join(hidden(&tmp_name.to_string_lossy()));
+
+    let result = (|| {
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)?;
+        sync_dir(&dir)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// Copies the current data file into the backup directory and prunes all but
+/// the newest `limit` backups. Does nothing if the file does not exist yet.
+pub(crate) fn rotate(path: &Path, limit: usize) -> io::Result<()> {
+    if limit == 0 || !path.exists() {
+        return Ok(());
+    }
+
+    let dir = backup_dir(path);
+    fs::create_dir_all(&dir)?;
+    let stamp = Local::now().format(TIMESTAMP_FORMAT);
+    let backup_path = dir.join(format!("{}.{}.bak", file_name(path), stamp));
+    write_atomic(&backup_path, &fs::read(path)?)?;
+
+    for stale in list(path)?.into_iter().skip(limit) {
+        fs::remove_file(stale.path)?;
+    }
+    Ok(())
+}
+
+/// Copies an unreadable data file aside so it can be inspected or recovered later.
+/// Returns the existing copy if an identical one was already quarantined.
+pub(crate) fn quarantine(path: &Path) -> io::Result<PathBuf> {
+    let content = fs::read(path)?;
+    let dir = parent_dir(path).join(hidden(&format!("{}.quarantine", file_name(path))));
+    fs::create_dir_all(&dir)?;
+
+    for entry in fs::read_dir(&dir)? {
+        let existing = entry?.path();
+        if fs::read(&existing)? == content {
+            return Ok(existing);
+        }
+    }
+
+    let stamp = Local::now().format(TIMESTAMP_FORMAT);
+    let quarantined = dir.join(format!("{}.{}.corrupt", file_name(path), stamp));
+    write_atomic(&quarantined, &content)?;
+    Ok(quarantined)
+}
+
+/// Backups of the data file at `path`, newest first.
+pub(crate) fn list(path: &Path) -> io::Result<Vec<Backup>> {
+    let dir = backup_dir(path);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let prefix = format!("{}.", file_name(path));
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let stamp = match name.strip_prefix(&prefix).and_then(|rest| rest.strip_suffix(".bak")) {
+            Some(stamp) => stamp,
+            None => continue,
+        };
+        let created_at = match NaiveDateTime::parse_from_str(stamp, TIMESTAMP_FORMAT)
+            .ok()
+            .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+        {
+            Some(created_at) => created_at,
+            None => continue,
+        };
+        backups.push(Backup { path: entry.path(), created_at });
+    }
+
+    backups.sort_by(|a, b| b.path.cmp(&a.path));
+    Ok(backups)
+}
+
+/// Path of a hidden file kept beside the data file, e.g. `.todos.json.journal`.
+pub(crate) fn sidecar(path: &Path, suffix: &str) -> PathBuf {
+    parent_dir(path).join(hidden(&format!("{}.{}", file_name(path), suffix)))
+}
+
+fn backup_dir(path: &Path) -> PathBuf {
+    parent_dir(path).join(hidden(&format!("{}.backups", file_name(path))))
+}
+
+/// Prefixes `name` with a dot unless it already is a hidden name like `.todo.json`.
+fn hidden(name: &str) -> String {
+    if name.starts_with('.') {
+        name.to_string()
+    } else {
+        format!(".{}", name)
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().unwrap_or_default().to_string_lossy().into_owned()
+}
+
+fn parent_dir(path: &Path) -> PathBuf {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> io::Result<()> {
+    Ok(())
+}
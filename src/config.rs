@@ -0,0 +1,76 @@
+//! The config file: `$TODO_CONFIG`, or `todo/config.toml` in the user config
+//! directory.
+//!
+//! ```toml
+//! backup_limit = 10 # backups kept next to the data file; 0 turns them off
+//!
+//! [theme]
+//! base = "monochrome"
+//! ```
+//!
+//! The `[theme]` table is described in [`crate::theme`].
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::backup::DEFAULT_BACKUP_LIMIT;
+use crate::error::{Error, Result};
+use crate::theme::Theme;
+
+/// Environment variable naming the config file.
+pub const CONFIG_ENV: &str = "TODO_CONFIG";
+
+/// Where the config file is looked for: `$TODO_CONFIG`, then `todo/config.toml`
+/// in the user config directory (`$XDG_CONFIG_HOME`, usually `~/.config`).
+pub fn config_path() -> PathBuf {
+    if let Some(path) = env::var_os(CONFIG_ENV).filter(|value| !value.is_empty()) {
+        return PathBuf::from(path);
+    }
+    match dirs::config_dir() {
+        Some(dir) => dir.join("todo").join("config.toml"),
+        None => PathBuf::from("config.toml"),
+    }
+}
+
+/// Settings read from the config file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub theme: Theme,
+    /// Number of timestamped backups kept of the data file; `0` disables them.
+    pub backup_limit: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config { theme: Theme::default(), backup_limit: DEFAULT_BACKUP_LIMIT }
+    }
+}
+
+impl Config {
+    /// Reads the config file at `path`; a missing file gives the defaults.
+    /// Errors name the file.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let content = fs::read_to_string(path)?;
+        Config::parse(&content).map_err(|err| Error::Invalid(format!("{}: {}", path.display(), err)))
+    }
+
+    /// Builds the settings from the text of a config file. Unknown settings are
+    /// rejected so that typos do not go unnoticed.
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut config: toml::Table = content.parse().map_err(|err: toml::de::Error| Error::Invalid(err.to_string().trim_end().to_string()))?;
+        let backup_limit = match config.remove("backup_limit") {
+            Some(toml::Value::Integer(limit)) => usize::try_from(limit)
+                .map_err(|_| Error::Invalid(format!("`backup_limit` must be 0 or more, not {}", limit)))?,
+            Some(_) => return Err(Error::Invalid("`backup_limit` must be a whole number".to_string())),
+            None => DEFAULT_BACKUP_LIMIT,
+        };
+        if let Some(key) = config.keys().find(|key| *key != "theme") {
+            return Err(Error::Invalid(format!("Unknown setting '{}' (expected backup_limit or [theme])", key)));
+        }
+        Ok(Config { theme: Theme::from_config(content)?, backup_limit })
+    }
+}
@@ -0,0 +1,143 @@
+use std::env;
+
+use chrono::{
+    DateTime, Datelike, Days, Duration, FixedOffset, Local, LocalResult, Months, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeZone, Weekday,
+};
+
+use crate::error::{Error, Result};
+
+/// Due dates without an explicit time fall at the end of the day.
+pub(crate) fn end_of_day() -> NaiveTime {
+    NaiveTime::from_hms_opt(23, 59, 59).unwrap()
+}
+
+/// A due date is an instant stored with the UTC offset of whoever set it, so a
+/// list shared across time zones keeps meaning the same moment.
+pub type DueDate = DateTime<FixedOffset>;
+
+/// Parses a due date relative to the current local day and pins it to the local
+/// time zone. See [`parse_due_date_from`] for the accepted forms.
+pub fn parse_due_date(input: &str) -> Result<DueDate> {
+    Ok(local_due(parse_due_date_from(input, Local::now().date_naive())?))
+}
+
+/// Interprets a wall-clock time in the local time zone (`TZ` or the system
+/// zone). Times skipped by a DST change move forward by an hour.
+pub fn local_due(naive: NaiveDateTime) -> DueDate {
+    let local = match Local.from_local_datetime(&naive) {
+        LocalResult::Single(local) | LocalResult::Ambiguous(local, _) => local,
+        LocalResult::None => Local
+            .from_local_datetime(&(naive + Duration::hours(1)))
+            .earliest()
+            .unwrap_or_else(Local::now),
+    };
+    local.fixed_offset()
+}
+
+/// The calendar day of `due` in the viewer's local time zone.
+pub fn local_date(due: DueDate) -> NaiveDate {
+    due.with_timezone(&Local).date_naive()
+}
+
+/// The IANA name of the local time zone, e.g. `Europe/Berlin`, if it can be found.
+pub fn local_zone() -> Option<String> {
+    env::var("TZ")
+        .ok()
+        .map(|tz| tz.trim_start_matches(':').to_string())
+        .filter(|tz| tz.contains('/'))
+        .or_else(|| iana_time_zone::get_timezone().ok())
+}
+
+/// Parses a due date as a local wall-clock time, resolving relative expressions against `today`.
+///
+/// Accepts `YYYY-MM-DD`, `today`, `tomorrow`, weekday names (`friday`, `fri`,
+/// `next friday`; always a day after today), `next week`, `next month`,
+/// `in 3 days` / `in 2 weeks` / `in 1 month`, and `eow` / `eom` / `eoy` for the
+/// end of the week, month or year. Any of these may be followed by a time
+/// (`14:00`), and `YYYY-MM-DDTHH:MM` is accepted too. Without a time the due
+/// date is the end of that day.
+pub fn parse_due_date_from(input: &str, today: NaiveDate) -> Result<NaiveDateTime> {
+    let invalid = || {
+        Error::Invalid(format!(
+            "Could not understand due date '{}' (try YYYY-MM-DD, today, tomorrow, friday, next week, in 3 days or eom, optionally followed by a time like 14:00)",
+            input
+        ))
+    };
+
+    let mut text = input.trim().to_lowercase();
+    // Treat the ISO form `2024-12-25T14:00` like `2024-12-25 14:00`.
+    if text.as_bytes().get(10) == Some(&b't') && text.get(..10).is_some_and(|date| date.contains('-')) {
+        text.replace_range(10..11, " ");
+    }
+    let mut words: Vec<&str> = text.split_whitespace().collect();
+
+    let time = match words.last().and_then(|word| NaiveTime::parse_from_str(word, "%H:%M").ok()) {
+        Some(time) => {
+            words.pop();
+            time
+        }
+        None => end_of_day(),
+    };
+
+    let date = match words[..] {
+        [] => return Err(invalid()),
+        [date] if date.contains('-') => NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| invalid())?,
+        ["today"] => today,
+        ["tomorrow"] => today.succ_opt().ok_or_else(invalid)?,
+        ["next", "week"] => today.checked_add_days(Days::new(7)).ok_or_else(invalid)?,
+        ["next", "month"] => today.checked_add_months(Months::new(1)).ok_or_else(invalid)?,
+        ["eow"] => today.checked_add_days(Days::new(u64::from(6 - today.weekday().num_days_from_monday()))).ok_or_else(invalid)?,
+        ["eom"] => last_day_of_month(today).ok_or_else(invalid)?,
+        ["eoy"] => NaiveDate::from_ymd_opt(today.year(), 12, 31).ok_or_else(invalid)?,
+        ["in", count, unit] => {
+            let count: u32 = count.parse().map_err(|_| invalid())?;
+            match unit.trim_end_matches('s') {
+                "day" => today.checked_add_days(Days::new(u64::from(count))).ok_or_else(invalid)?,
+                "week" => today.checked_add_days(Days::new(7 * u64::from(count))).ok_or_else(invalid)?,
+                "month" => today.checked_add_months(Months::new(count)).ok_or_else(invalid)?,
+                _ => return Err(invalid()),
+            }
+        }
+        [day] | ["next", day] => next_weekday(today, weekday(day).ok_or_else(invalid)?).ok_or_else(invalid)?,
+        _ => return Err(invalid()),
+    };
+
+    Ok(date.and_time(time))
+}
+
+/// Formats a due date in the viewer's local time zone as `YYYY-MM-DD`, adding
+/// the time unless it falls at the default end of day.
+pub fn format_due_date(due: DueDate) -> String {
+    let due = due.with_timezone(&Local);
+    if due.time() == end_of_day() {
+        due.format("%Y-%m-%d").to_string()
+    } else {
+        due.format("%Y-%m-%d %H:%M").to_string()
+    }
+}
+
+pub(crate) fn weekday(name: &str) -> Option<Weekday> {
+    match name {
+        "mon" | "monday" => Some(Weekday::Mon),
+        "tue" | "tues" | "tuesday" => Some(Weekday::Tue),
+        "wed" | "wednesday" => Some(Weekday::Wed),
+        "thu" | "thur" | "thurs" | "thursday" => Some(Weekday::Thu),
+        "fri" | "friday" => Some(Weekday::Fri),
+        "sat" | "saturday" => Some(Weekday::Sat),
+        "sun" | "sunday" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// The first `weekday` strictly after `today`.
+fn next_weekday(today: NaiveDate, weekday: Weekday) -> Option<NaiveDate> {
+    let ahead = (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+    today.checked_add_days(Days::new(u64::from(if ahead == 0 { 7 } else { ahead })))
+}
+
+/// The last day of the month `day` is in; `None` in the last representable month.
+pub(crate) fn last_day_of_month(day: NaiveDate) -> Option<NaiveDate> {
+    let first = day.with_day(1).unwrap();
+    first.checked_add_months(Months::new(1))?.pred_opt()
+}
@@ -0,0 +1,40 @@
+use crate::todo::Todo;
+
+/// The chain of prerequisites leading from `from` to `to`, both included, if
+/// `from` already depends on `to` directly or through other tasks.
+pub(crate) fn path(todos: &[Todo], from: usize, to: usize) -> Option<Vec<usize>> {
+    // Breadth-first over `depends_on`, remembering how each task was reached.
+    let mut reached: Vec<(usize, usize)> = vec![(from, from)];
+    let mut next = 0;
+    while next < reached.len() {
+        let (id, _) = reached[next];
+        if id == to {
+            let mut path = vec![to];
+            let mut current = to;
+            while current != from {
+                current = reached.iter().find(|(id, _)| *id == current).unwrap().1;
+                path.push(current);
+            }
+            path.reverse();
+            return Some(path);
+        }
+        let prerequisites = todos.iter().find(|todo| todo.id == id).map_or(&[][..], |todo| &todo.depends_on[..]);
+        for &prerequisite in prerequisites {
+            if !reached.iter().any(|(seen, _)| *seen == prerequisite) {
+                reached.push((prerequisite, id));
+            }
+        }
+        next += 1;
+    }
+    None
+}
+
+/// Direct prerequisites of `todo` that are still open. Prerequisites that were
+/// deleted or cancelled no longer hold it up.
+pub(crate) fn open_prerequisites(todos: &[Todo], todo: &Todo) -> Vec<usize> {
+    todo.depends_on
+        .iter()
+        .copied()
+        .filter(|&id| todos.iter().any(|other| other.id == id && other.status.is_open()))
+        .collect()
+}
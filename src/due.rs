@@ -0,0 +1,63 @@
+//! `todo due`: which open todos are overdue, due today or coming up soon.
+
+use chrono::NaiveDate;
+
+use crate::sort::{sort_todos, SortField, SortKey};
+use crate::todo::Todo;
+
+/// The status `todo due --exit-code` exits with when a todo is overdue.
+pub const OVERDUE_EXIT_CODE: i32 = 2;
+
+/// Open todos due within a window of days, split by how close their due date is.
+#[derive(Debug, Default)]
+pub struct DueReport<'a> {
+    pub overdue: Vec<&'a Todo>,
+    pub today: Vec<&'a Todo>,
+    /// Due after today, at most the requested number of days ahead.
+    pub upcoming: Vec<&'a Todo>,
+}
+
+impl<'a> DueReport<'a> {
+    /// Open `todos` due no more than `days` after `today`, soonest first.
+    /// Due dates are compared as days in the local time zone.
+    pub fn new(todos: &'a [Todo], today: NaiveDate, days: i64) -> Self {
+        let mut due: Vec<&Todo> = todos.iter().filter(|todo| todo.status.is_open()).collect();
+        sort_todos(&mut due, &[SortKey { field: SortField::Due, descending: false }]);
+
+        let mut report = DueReport::default();
+        for todo in due {
+            match todo.days_until_due(today) {
+                Some(left) if left < 0 => report.overdue.push(todo),
+                Some(0) => report.today.push(todo),
+                Some(left) if left <= days => report.upcoming.push(todo),
+                _ => {}
+            }
+        }
+        report
+    }
+
+    /// Every todo in the report, overdue first and soonest first.
+    pub fn todos(&self) -> Vec<&'a Todo> {
+        self.overdue.iter().chain(&self.today).chain(&self.upcoming).copied().collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.overdue.is_empty() && self.today.is_empty() && self.upcoming.is_empty()
+    }
+
+    /// [`OVERDUE_EXIT_CODE`] when anything is overdue, otherwise `None`.
+    pub fn exit_code(&self) -> Option<i32> {
+        if self.overdue.is_empty() { None } else { Some(OVERDUE_EXIT_CODE) }
+    }
+}
+
+/// Describes a due date `days` after today, e.g. "due in 2 days" or "3 days overdue".
+pub fn relative_due(days: i64) -> String {
+    match days {
+        0 => "due today".to_string(),
+        1 => "due tomorrow".to_string(),
+        -1 => "1 day overdue".to_string(),
+        days if days < 0 => format!("{} days overdue", -days),
+        days => format!("due in {} days", days),
+    }
+}
@@ -0,0 +1,115 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use crate::todo::Status;
+
+/// Errors returned by [`TodoList`](crate::TodoList) operations.
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Json(serde_json::Error),
+    NotFound(usize),
+    /// User input that could not be understood, with a description of the problem.
+    Invalid(String),
+    /// A filter expression failed to parse; `position` is the offending character.
+    Query {
+        input: String,
+        position: usize,
+        message: String,
+    },
+    /// The todo with this id already has the requested status.
+    AlreadyInStatus(usize, Status),
+    /// The todo still has these subtasks and the requested [`ChildPolicy`](crate::ChildPolicy)
+    /// is to refuse.
+    HasSubtasks(usize, Vec<usize>),
+    /// A dependency would close this loop of task ids, which starts and ends
+    /// with the same task.
+    DependencyCycle(Vec<usize>),
+    BackupNotFound(usize),
+    NothingToUndo,
+    NothingToRedo,
+    /// The data file uses a schema version this build cannot read.
+    UnsupportedSchema(u32),
+    /// The data file exists but could not be parsed. It is left untouched and a
+    /// copy is placed in quarantine when possible.
+    Corrupt {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        message: String,
+        quarantined: Option<PathBuf>,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "I/O error: {}", err),
+            Error::Json(err) => write!(f, "JSON error: {}", err),
+            Error::NotFound(id) => write!(f, "Todo with id {} not found", id),
+            Error::Invalid(message) => f.write_str(message),
+            Error::Query { input, position, message } => write!(
+                f,
+                "Invalid query at column {}: {}\n    {}\n    {}^",
+                position + 1,
+                message,
+                input,
+                " ".repeat(*position)
+            ),
+            Error::AlreadyInStatus(id, status) => write!(f, "Task {} is already {}", id, status.as_str()),
+            Error::HasSubtasks(id, children) => {
+                let children: Vec<String> = children.iter().map(|child| format!("#{}", child)).collect();
+                write!(f, "Task {} has subtasks {}; cascade to them or orphan them first", id, children.join(", "))
+            }
+            Error::DependencyCycle(cycle) => {
+                let cycle: Vec<String> = cycle.iter().map(|id| format!("#{}", id)).collect();
+                write!(f, "That dependency would create a cycle: {}", cycle.join(" -> "))
+            }
+            Error::BackupNotFound(index) => write!(f, "No backup number {}", index + 1),
+            Error::NothingToUndo => f.write_str("Nothing to undo"),
+            Error::NothingToRedo => f.write_str("Nothing to redo"),
+            Error::UnsupportedSchema(version) => write!(
+                f,
+                "Unsupported schema version {} (this build reads up to {}); upgrade todo to open this file",
+                version,
+                crate::schema::CURRENT_VERSION
+            ),
+            Error::Corrupt { path, line, column, message, quarantined } => {
+                write!(f, "{} is corrupt", path.display())?;
+                if *line > 0 {
+                    write!(f, " at line {}, column {}", line, column)?;
+                }
+                write!(f, ": {}", message)?;
+                if let Some(copy) = quarantined {
+                    write!(f, " (copy saved to {})", copy.display())?;
+                }
+                write!(f, "; it will not be overwritten. Run `todo doctor` to recover it")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            Error::Json(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
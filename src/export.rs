@@ -0,0 +1,266 @@
+//! Machine-readable output for scripts: JSON, NDJSON, CSV, TSV and templates.
+//!
+//! Every format is built from the same [`Record`], whose JSON shape is stable:
+//! fields may be added in later versions, but existing ones keep their name,
+//! type and meaning. Dates are RFC 3339 with the offset they were recorded in.
+
+use std::io::Write;
+
+use chrono::SecondsFormat;
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::todo::{ChecklistItem, Todo};
+
+/// Fields available as CSV/TSV columns and template placeholders, in column
+/// order. `score` is only filled in for search results.
+pub const FIELDS: &[&str] = &[
+    "id", "uuid", "title", "status", "priority", "tags", "due", "due_zone", "created", "completed", "recurrence",
+    "parent", "depends_on", "notes", "score",
+];
+
+/// How read commands print their results.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Format {
+    /// Colored text for people.
+    #[default]
+    Text,
+    /// One pretty-printed JSON array of records.
+    Json,
+    /// One compact JSON record per line.
+    Ndjson,
+    /// Comma-separated values with a header row.
+    Csv,
+    /// Tab-separated values with a header row; tabs, newlines and backslashes
+    /// in values are escaped as `\t`, `\n` and `\\`.
+    Tsv,
+    /// One line per record from a template like `{id}\t{title}\t{due}`.
+    Template(Template),
+}
+
+impl Format {
+    /// Parses `text`, `json`, `ndjson`, `csv`, `tsv`, or a template containing
+    /// at least one `{field}`.
+    pub fn parse(format: &str) -> Result<Self> {
+        match format {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "ndjson" => Ok(Format::Ndjson),
+            "csv" => Ok(Format::Csv),
+            "tsv" => Ok(Format::Tsv),
+            template if template.contains('{') => Ok(Format::Template(Template::parse(template)?)),
+            _ => Err(Error::Invalid(format!(
+                "Unknown format '{}' (expected text, json, ndjson, csv, tsv or a template like '{{id}}\\t{{title}}')",
+                format
+            ))),
+        }
+    }
+
+    pub fn is_text(&self) -> bool {
+        *self == Format::Text
+    }
+
+    /// Writes `records` to `out` in any format but [`Format::Text`], which the
+    /// CLI renders itself.
+    pub fn write(&self, out: &mut impl Write, records: &[Record]) -> Result<()> {
+        match self {
+            Format::Text => return Err(Error::Invalid("Text output is printed by the CLI".to_string())),
+            Format::Json => writeln!(out, "{}", serde_json::to_string_pretty(records)?)?,
+            Format::Ndjson => {
+                for record in records {
+                    writeln!(out, "{}", serde_json::to_string(record)?)?;
+                }
+            }
+            Format::Csv | Format::Tsv => {
+                let (separator, escape): (&str, fn(&str) -> String) =
+                    if *self == Format::Csv { (",", csv_escape) } else { ("\t", tsv_escape) };
+                writeln!(out, "{}", FIELDS.join(separator))?;
+                for record in records {
+                    let row: Vec<String> = FIELDS.iter().map(|column| escape(&record.field(column))).collect();
+                    writeln!(out, "{}", row.join(separator))?;
+                }
+            }
+            Format::Template(template) => {
+                for record in records {
+                    writeln!(out, "{}", template.render(record))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A parsed `--format` template: literal text and `{field}` placeholders.
+///
+/// `\t`, `\n` and `\\` are unescaped so the template can be typed in single
+/// quotes; `{{` and `}}` stand for literal braces.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Field(String),
+}
+
+impl Template {
+    pub fn parse(template: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('t') => text.push('\t'),
+                    Some('n') => text.push('\n'),
+                    Some('\\') => text.push('\\'),
+                    Some(other) => {
+                        text.push('\\');
+                        text.push(other);
+                    }
+                    None => text.push('\\'),
+                },
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let name: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                    if !FIELDS.contains(&name.as_str()) {
+                        return Err(Error::Invalid(format!(
+                            "Unknown field '{{{}}}' in format template (expected one of {})",
+                            name,
+                            FIELDS.join(", ")
+                        )));
+                    }
+                    parts.push(Part::Text(std::mem::take(&mut text)));
+                    parts.push(Part::Field(name));
+                }
+                c => text.push(c),
+            }
+        }
+        parts.push(Part::Text(text));
+        Ok(Template { parts })
+    }
+
+    pub fn render(&self, record: &Record) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => text.clone(),
+                Part::Field(name) => record.field(name),
+            })
+            .collect()
+    }
+}
+
+/// One todo as scripts see it. The JSON field names and types are stable.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Record {
+    pub id: usize,
+    pub uuid: Option<String>,
+    pub title: String,
+    /// `todo`, `in-progress`, `blocked`, `done` or `cancelled`.
+    pub status: String,
+    /// `high`, `medium` or `low`.
+    pub priority: String,
+    pub tags: Vec<String>,
+    pub due: Option<String>,
+    /// The IANA time zone the due date was set in, when known.
+    pub due_zone: Option<String>,
+    pub created: String,
+    pub completed: Option<String>,
+    /// An RFC 5545 RRULE, e.g. `FREQ=WEEKLY;INTERVAL=1;BYDAY=MO`.
+    pub recurrence: Option<String>,
+    pub parent: Option<usize>,
+    pub depends_on: Vec<usize>,
+    /// Markdown.
+    pub notes: String,
+    pub checklist: Vec<ChecklistItem>,
+    /// Search relevance, higher is better; only present in search results.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<u32>,
+}
+
+impl Record {
+    /// A search result with its relevance score.
+    pub fn scored(todo: &Todo, score: u32) -> Self {
+        Record { score: Some(score), ..Record::from(todo) }
+    }
+
+    /// The value of one of [`FIELDS`] as flat text: lists are
+    /// comma-separated and missing values are empty.
+    pub fn field(&self, name: &str) -> String {
+        let join = |ids: &[usize]| ids.iter().map(usize::to_string).collect::<Vec<_>>().join(",");
+        match name {
+            "id" => self.id.to_string(),
+            "uuid" => self.uuid.clone().unwrap_or_default(),
+            "title" => self.title.clone(),
+            "status" => self.status.clone(),
+            "priority" => self.priority.clone(),
+            "tags" => self.tags.join(","),
+            "due" => self.due.clone().unwrap_or_default(),
+            "due_zone" => self.due_zone.clone().unwrap_or_default(),
+            "created" => self.created.clone(),
+            "completed" => self.completed.clone().unwrap_or_default(),
+            "recurrence" => self.recurrence.clone().unwrap_or_default(),
+            "parent" => self.parent.map(|id| id.to_string()).unwrap_or_default(),
+            "depends_on" => join(&self.depends_on),
+            "notes" => self.notes.clone(),
+            "score" => self.score.map(|score| score.to_string()).unwrap_or_default(),
+            _ => String::new(),
+        }
+    }
+}
+
+impl From<&Todo> for Record {
+    fn from(todo: &Todo) -> Self {
+        Record {
+            id: todo.id,
+            uuid: todo.uuid.map(|uuid| uuid.to_string()),
+            title: todo.title.clone(),
+            status: todo.status.as_str().to_string(),
+            priority: todo.priority.as_str().to_string(),
+            tags: todo.categories.clone(),
+            due: todo.due_date.map(|due| due.to_rfc3339_opts(SecondsFormat::Secs, false)),
+            due_zone: todo.due_zone.clone(),
+            created: todo.created_at.to_rfc3339_opts(SecondsFormat::Secs, false),
+            completed: todo.completed_at.map(|at| at.to_rfc3339_opts(SecondsFormat::Secs, false)),
+            recurrence: todo.recurrence.as_ref().map(|rule| rule.to_string()),
+            parent: todo.parent_id,
+            depends_on: todo.depends_on.clone(),
+            notes: todo.notes.clone(),
+            checklist: todo.checklist.clone(),
+            score: None,
+        }
+    }
+}
+
+/// Quotes a CSV value when it contains a separator, quote or line break.
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn tsv_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
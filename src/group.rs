@@ -0,0 +1,109 @@
+use chrono::{Datelike, Duration, NaiveDate};
+
+use crate::dates::local_date;
+use crate::error::{Error, Result};
+use crate::todo::{Priority, Status, Todo};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GroupBy {
+    Priority,
+    /// A todo appears under each of its tags.
+    Tag,
+    Due,
+    Status,
+}
+
+impl GroupBy {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "priority" => Ok(GroupBy::Priority),
+            "tag" => Ok(GroupBy::Tag),
+            "due" => Ok(GroupBy::Due),
+            "status" => Ok(GroupBy::Status),
+            _ => Err(Error::Invalid(format!("Unknown grouping '{}' (expected priority, tag, due or status)", name))),
+        }
+    }
+}
+
+/// How close a due date is, relative to a given day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DueBucket {
+    Overdue,
+    Today,
+    /// After today, up to and including Sunday of the current week.
+    ThisWeek,
+    Later,
+    NoDueDate,
+}
+
+impl DueBucket {
+    pub fn of(todo: &Todo, today: NaiveDate) -> Self {
+        let due = match todo.due_date {
+            Some(due) => local_date(due),
+            None => return DueBucket::NoDueDate,
+        };
+        let end_of_week = today + Duration::days(6 - i64::from(today.weekday().num_days_from_monday()));
+        if due < today {
+            DueBucket::Overdue
+        } else if due == today {
+            DueBucket::Today
+        } else if due <= end_of_week {
+            DueBucket::ThisWeek
+        } else {
+            DueBucket::Later
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DueBucket::Overdue => "Overdue",
+            DueBucket::Today => "Today",
+            DueBucket::ThisWeek => "This week",
+            DueBucket::Later => "Later",
+            DueBucket::NoDueDate => "No due date",
+        }
+    }
+}
+
+/// A titled run of todos produced by [`group_todos`].
+#[derive(Debug)]
+pub struct Group<'a> {
+    pub title: String,
+    pub todos: Vec<&'a Todo>,
+}
+
+/// Splits `todos` into groups in a fixed order (priority high to low, status
+/// in lifecycle order, tags alphabetically, due buckets soonest first), keeping
+/// the incoming order within each group. Empty groups are omitted.
+pub fn group_todos<'a>(todos: &[&'a Todo], by: GroupBy, today: NaiveDate) -> Vec<Group<'a>> {
+    let mut groups: Vec<(String, Vec<&'a Todo>)> = match by {
+        GroupBy::Priority => [Priority::High, Priority::Medium, Priority::Low]
+            .iter()
+            .map(|priority| (priority.as_str().to_string(), todos.iter().copied().filter(|t| t.priority == *priority).collect()))
+            .collect(),
+        GroupBy::Status => [Status::Todo, Status::InProgress, Status::Blocked, Status::Done, Status::Cancelled]
+            .iter()
+            .map(|status| (status.as_str().to_string(), todos.iter().copied().filter(|t| t.status == *status).collect()))
+            .collect(),
+        GroupBy::Due => [DueBucket::Overdue, DueBucket::Today, DueBucket::ThisWeek, DueBucket::Later, DueBucket::NoDueDate]
+            .iter()
+            .map(|bucket| {
+                (bucket.label().to_string(), todos.iter().copied().filter(|t| DueBucket::of(t, today) == *bucket).collect())
+            })
+            .collect(),
+        GroupBy::Tag => {
+            let mut tags: Vec<&String> = todos.iter().flat_map(|t| &t.categories).collect();
+            tags.sort();
+            tags.dedup();
+            let mut groups: Vec<(String, Vec<&'a Todo>)> = tags
+                .into_iter()
+                .map(|tag| (tag.clone(), todos.iter().copied().filter(|t| t.categories.contains(tag)).collect()))
+                .collect();
+            groups.push(("(untagged)".to_string(), todos.iter().copied().filter(|t| t.categories.is_empty()).collect()));
+            groups
+        }
+    };
+
+    groups.retain(|(_, todos)| !todos.is_empty());
+    groups.into_iter().map(|(title, todos)| Group { title, todos }).collect()
+}
@@ -0,0 +1,45 @@
+use std::collections::HashSet;
+
+use crate::error::{Error, Result};
+
+/// Parses a comma-separated list of ids and inclusive ranges, e.g. `1,4,7-10`.
+/// Duplicates are dropped; the order of first appearance is kept.
+///
+/// Ids named one by one are kept whether or not they exist, so that a typo is
+/// reported. Ranges only pick the ids in `in_use`: deleted ids are never handed
+/// out again, so gaps are normal, and a range may reach past the newest todo.
+pub fn parse_ids(spec: &str, in_use: &[usize]) -> Result<Vec<usize>> {
+    let invalid = |part: &str| Error::Invalid(format!("Invalid id or range '{}' (expected e.g. 1,4,7-10)", part));
+
+    let mut in_use = in_use.to_vec();
+    in_use.sort_unstable();
+    let mut ids = Vec::new();
+    let mut seen = HashSet::new();
+    let mut parts = 0;
+    for part in spec.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+        let (start, end) = match part.split_once('-') {
+            Some((start, end)) => (start.trim(), end.trim()),
+            None => (part, part),
+        };
+        let start: usize = start.parse().map_err(|_| invalid(part))?;
+        let end: usize = end.parse().map_err(|_| invalid(part))?;
+        if start == 0 || start > end {
+            return Err(invalid(part));
+        }
+        parts += 1;
+        if start == end {
+            if seen.insert(start) {
+                ids.push(start);
+            }
+            continue;
+        }
+        let first = in_use.partition_point(|&id| id < start);
+        let last = in_use.partition_point(|&id| id <= end);
+        ids.extend(in_use[first..last].iter().copied().filter(|&id| seen.insert(id)));
+    }
+
+    if parts == 0 {
+        return Err(invalid(spec));
+    }
+    Ok(ids)
+}
@@ -0,0 +1,160 @@
+//! A persisted inverted index for [`Search::Fuzzy`](crate::Search::Fuzzy).
+//!
+//! The index lives in a hidden `.<file>.index` next to the data file and maps
+//! every word of every title, tag and notes to the ids containing it, plus each
+//! word's English stem to the words sharing it. Each todo's text is
+//! fingerprinted, so [`SearchIndex::update`] only re-tokenizes todos that
+//! changed since the index was written. A missing, unreadable or outdated
+//! index is rebuilt from scratch.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rust_stemmers::{Algorithm, Stemmer};
+use serde::{Deserialize, Serialize};
+
+use crate::backup;
+use crate::error::Result;
+use crate::search::{self, Search};
+use crate::todo::Todo;
+
+/// Bumped whenever tokenizing or stemming changes, which invalidates old indexes.
+const INDEX_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct Entry {
+    fingerprint: u64,
+    words: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub(crate) struct SearchIndex {
+    version: u32,
+    /// Indexed todos by id.
+    entries: HashMap<usize, Entry>,
+    /// Word to the ids of the todos containing it.
+    postings: BTreeMap<String, BTreeSet<usize>>,
+    /// Stem to the indexed words that reduce to it.
+    stems: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Default for SearchIndex {
+    fn default() -> Self {
+        SearchIndex { version: INDEX_VERSION, entries: HashMap::new(), postings: BTreeMap::new(), stems: BTreeMap::new() }
+    }
+}
+
+impl SearchIndex {
+    /// Reads the index stored next to `data_file`, or an empty one if there is
+    /// none or it cannot be used.
+    pub(crate) fn load(data_file: &Path) -> Self {
+        fs::read_to_string(path(data_file))
+            .ok()
+            .and_then(|content| serde_json::from_str::<SearchIndex>(&content).ok())
+            .filter(|index| index.version == INDEX_VERSION)
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn exists(&self, data_file: &Path) -> bool {
+        path(data_file).exists()
+    }
+
+    pub(crate) fn save(&self, data_file: &Path) -> Result<()> {
+        backup::write_atomic(&path(data_file), serde_json::to_string(self)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Brings the index in line with `todos`, re-indexing only those whose text
+    /// changed. Returns whether anything changed.
+    pub(crate) fn update(&mut self, todos: &[Todo]) -> bool {
+        let stemmer = Stemmer::create(Algorithm::English);
+        let mut changed = false;
+
+        let live: HashSet<usize> = todos.iter().map(|todo| todo.id).collect();
+        let gone: Vec<usize> = self.entries.keys().copied().filter(|id| !live.contains(id)).collect();
+        for id in gone {
+            self.remove(id, &stemmer);
+            changed = true;
+        }
+
+        for todo in todos {
+            let fingerprint = fingerprint(todo);
+            if self.entries.get(&todo.id).is_some_and(|entry| entry.fingerprint == fingerprint) {
+                continue;
+            }
+            self.remove(todo.id, &stemmer);
+            let words: BTreeSet<String> = text_of(todo).flat_map(search::words).map(|(_, _, word)| word).collect();
+            for word in &words {
+                self.postings.entry(word.clone()).or_default().insert(todo.id);
+                self.stems.entry(stemmer.stem(word).into_owned()).or_default().insert(word.clone());
+            }
+            self.entries.insert(todo.id, Entry { fingerprint, words: words.into_iter().collect() });
+            changed = true;
+        }
+        changed
+    }
+
+    /// Ids of the todos that can match `search`, or `None` when the index cannot
+    /// narrow it down. Every todo [`Search::run`] would return is included.
+    pub(crate) fn candidates(&self, search: &Search) -> Option<HashSet<usize>> {
+        let terms = match search {
+            Search::Fuzzy(terms) => terms,
+            Search::Regex(_) => return None,
+        };
+        let stemmer = Stemmer::create(Algorithm::English);
+        let mut candidates: Option<HashSet<usize>> = None;
+        for term in terms {
+            let stem = stemmer.stem(term);
+            let mut ids = HashSet::new();
+            let same_stem = self.stems.get(stem.as_ref()).into_iter().flatten();
+            let similar = self.postings.keys().filter(|word| search::quality(term, word) > 0);
+            for word in same_stem.chain(similar) {
+                ids.extend(self.postings.get(word).into_iter().flatten().copied());
+            }
+            candidates = Some(match candidates {
+                Some(previous) => previous.intersection(&ids).copied().collect(),
+                None => ids,
+            });
+        }
+        Some(candidates.unwrap_or_default())
+    }
+
+    fn remove(&mut self, id: usize, stemmer: &Stemmer) {
+        let Some(entry) = self.entries.remove(&id) else { return };
+        for word in entry.words {
+            let Some(ids) = self.postings.get_mut(&word) else { continue };
+            ids.remove(&id);
+            if ids.is_empty() {
+                self.postings.remove(&word);
+                let stem = stemmer.stem(&word).into_owned();
+                if let Some(words) = self.stems.get_mut(&stem) {
+                    words.remove(&word);
+                    if words.is_empty() {
+                        self.stems.remove(&stem);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn path(data_file: &Path) -> PathBuf {
+    backup::sidecar(data_file, "index")
+}
+
+fn text_of(todo: &Todo) -> impl Iterator<Item = &str> {
+    std::iter::once(todo.title.as_str()).chain(todo.categories.iter().map(String::as_str)).chain([todo.notes.as_str()])
+}
+
+/// FNV-1a over the searchable text, stable across builds unlike `DefaultHasher`.
+fn fingerprint(todo: &Todo) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for text in text_of(todo) {
+        for byte in text.bytes().chain([0]) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
@@ -0,0 +1,115 @@
+//! `todo edit --interactive`: round-trips a task through `$EDITOR` as TOML.
+
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+use todo_list::{format_due_date, parse_due_date, Error, Priority, Recurrence, Result, Todo, TodoEdit};
+
+const HEADER: &str = "\
+# Edit the task below, save and quit. Leave the file unchanged to abort.
+# priority: high, medium or low. due: YYYY-MM-DD [HH:MM], tomorrow, friday, ...; delete the line to clear it.
+# repeat: daily, weekly on mon,thu, monthly on 15, every 10 days after done, ...; delete the line to stop repeating.
+# notes: Markdown; use a '''multi-line''' string for several lines.
+";
+
+#[derive(Serialize, Deserialize)]
+struct EditableTodo {
+    title: String,
+    priority: String,
+    due: Option<String>,
+    repeat: Option<String>,
+    tags: Vec<String>,
+    #[serde(default)]
+    notes: String,
+}
+
+/// Opens `todo` in the user's editor and returns the changes they made.
+pub fn edit(todo: &Todo) -> Result<TodoEdit> {
+    let editable = EditableTodo {
+        title: todo.title.clone(),
+        priority: todo.priority.as_str().to_string(),
+        due: todo.due_date.map(format_due_date),
+        repeat: todo.recurrence.as_ref().map(Recurrence::describe),
+        tags: todo.categories.clone(),
+        notes: todo.notes.clone(),
+    };
+    let original = format!("{}\n{}", HEADER, toml::to_string(&editable).map_err(|err| Error::Invalid(err.to_string()))?);
+
+    let (path, mut file) = create_temp_file(todo.id)?;
+    let written = file.write_all(original.as_bytes());
+    drop(file);
+    let result = written.map_err(Error::from)
+        .and_then(|_| run_editor(&path))
+        .and_then(|_| Ok(fs::read_to_string(&path)?));
+    let _ = fs::remove_file(&path);
+    let content = result?;
+
+    if content == original {
+        return Ok(TodoEdit::default());
+    }
+
+    let edited: EditableTodo = toml::from_str(&content)
+        .map_err(|err| Error::Invalid(format!("Could not read edited task: {}", err)))?;
+
+    let priority = Priority::from_name(edited.priority.trim())
+        .ok_or_else(|| Error::Invalid(format!("Unknown priority '{}' (expected high, medium or low)", edited.priority)))?;
+    let due_date = match edited.due.as_deref().map(str::trim).filter(|due| !due.is_empty()) {
+        Some(due) => Some(parse_due_date(due)?),
+        None => None,
+    };
+    let recurrence = match edited.repeat.as_deref().map(str::trim).filter(|repeat| !repeat.is_empty()) {
+        Some(repeat) => Some(Recurrence::parse(repeat)?),
+        None => None,
+    };
+
+    Ok(TodoEdit {
+        title: Some(edited.title.trim().to_string()).filter(|title| *title != todo.title),
+        priority: Some(priority).filter(|priority| *priority != todo.priority),
+        due_date: Some(due_date).filter(|due| *due != todo.due_date),
+        add_categories: edited.tags.iter().filter(|tag| !todo.categories.contains(tag)).cloned().collect(),
+        remove_categories: todo.categories.iter().filter(|tag| !edited.tags.contains(tag)).cloned().collect(),
+        recurrence: Some(recurrence).filter(|recurrence| *recurrence != todo.recurrence),
+        parent_id: None,
+        notes: Some(edited.notes.trim_end().to_string()).filter(|notes| *notes != todo.notes),
+    })
+}
+
+/// Creates a fresh, unpredictably named file for the editor, refusing to
+/// reuse anything (or follow any symlink) already at that path.
+fn create_temp_file(id: usize) -> Result<(PathBuf, File)> {
+    let path = env::temp_dir().join(format!("todo-edit-{}-{}.toml", id, uuid::Uuid::new_v4().simple()));
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let file = options.open(&path)?;
+    Ok((path, file))
+}
+
+/// `$VISUAL`, then `$EDITOR`, then `vi`; blank variables count as unset.
+fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string())
+}
+
+fn run_editor(path: &Path) -> Result<()> {
+    let editor = editor_command();
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+
+    let status = Command::new(program).args(words).arg(path).status()?;
+    if !status.success() {
+        return Err(Error::Invalid(format!("Editor '{}' exited with {}", editor, status)));
+    }
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::backup;
+use crate::todo::Todo;
+
+/// Number of operations kept for undo; older ones are dropped.
+pub const JOURNAL_LIMIT: usize = 100;
+
+/// One todo's state on either side of an operation. `before` is `None` for an
+/// added todo and `after` is `None` for a deleted one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Change {
+    /// Position of the todo in the list where it existed (after for adds,
+    /// before otherwise), so undoing a delete puts it back in place.
+    pub index: usize,
+    pub before: Option<Todo>,
+    pub after: Option<Todo>,
+}
+
+/// A single mutating command, e.g. `complete #3`, and everything it changed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Operation {
+    pub description: String,
+    pub at: DateTime<Local>,
+    pub changes: Vec<Change>,
+}
+
+/// The undo/redo history, stored beside the data file.
+///
+/// `operations[..position]` have been applied and can be undone, in reverse;
+/// `operations[position..]` were undone and can be redone.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Journal {
+    pub operations: Vec<Operation>,
+    pub position: usize,
+}
+
+impl Journal {
+    /// Loads the journal for the data file at `data_path`. The journal is only a
+    /// convenience, so a missing or unreadable one starts an empty history.
+    pub(crate) fn load(data_path: &Path) -> Self {
+        fs::read_to_string(journal_path(data_path))
+            .ok()
+            .and_then(|content| serde_json::from_str::<Journal>(&content).ok())
+            .filter(|journal| journal.position <= journal.operations.len())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn save(&self, data_path: &Path) -> crate::Result<()> {
+        let content = serde_json::to_string(self)?;
+        backup::write_atomic(&journal_path(data_path), content.as_bytes())?;
+        Ok(())
+    }
+
+    /// Records an operation, discarding anything that could have been redone.
+    /// Operations that changed nothing are not recorded.
+    pub(crate) fn record(&mut self, description: String, before: &[Todo], after: &[Todo]) {
+        let changes = diff(before, after);
+        if changes.is_empty() {
+            return;
+        }
+
+        self.operations.truncate(self.position);
+        self.operations.push(Operation { description, at: Local::now(), changes });
+        if self.operations.len() > JOURNAL_LIMIT {
+            self.operations.remove(0);
+        }
+        self.position = self.operations.len();
+    }
+
+    /// Reverts the most recent applied operation on `todos` and returns it.
+    pub(crate) fn undo(&mut self, todos: &mut Vec<Todo>) -> Option<&Operation> {
+        self.position = self.position.checked_sub(1)?;
+        let operation = &self.operations[self.position];
+        // Deleted todos go back last, in ascending order, so each lands at its old index.
+        let (deleted, others): (Vec<&Change>, Vec<&Change>) =
+            operation.changes.iter().partition(|change| change.after.is_none());
+        for change in others.into_iter().chain(deleted) {
+            apply(todos, change.index, change.after.as_ref(), change.before.as_ref());
+        }
+        Some(operation)
+    }
+
+    /// Re-applies the most recently undone operation on `todos` and returns it.
+    pub(crate) fn redo(&mut self, todos: &mut Vec<Todo>) -> Option<&Operation> {
+        let operation = self.operations.get(self.position)?;
+        self.position += 1;
+        for change in &operation.changes {
+            apply(todos, change.index, change.before.as_ref(), change.after.as_ref());
+        }
+        Some(operation)
+    }
+}
+
+fn journal_path(data_path: &Path) -> PathBuf {
+    backup::sidecar(data_path, "journal")
+}
+
+/// Replaces the todo in state `from` with its state `to`.
+fn apply(todos: &mut Vec<Todo>, index: usize, from: Option<&Todo>, to: Option<&Todo>) {
+    let id = from.or(to).map(|todo| todo.id).unwrap_or_default();
+    let position = todos.iter().position(|todo| todo.id == id);
+    match (position, to) {
+        (Some(position), Some(to)) => todos[position] = to.clone(),
+        (Some(position), None) => {
+            todos.remove(position);
+        }
+        (None, Some(to)) => todos.insert(index.min(todos.len()), to.clone()),
+        (None, None) => {}
+    }
+}
+
+/// Compares two versions of the list by id. Deletions and modifications come
+/// first in old-list order, then additions in new-list order, which is the
+/// order [`Journal::redo`] replays them in.
+fn diff(before: &[Todo], after: &[Todo]) -> Vec<Change> {
+    let old: HashMap<usize, (usize, &Todo)> = before.iter().enumerate().map(|(i, t)| (t.id, (i, t))).collect();
+    let new: HashMap<usize, (usize, &Todo)> = after.iter().enumerate().map(|(i, t)| (t.id, (i, t))).collect();
+
+    let mut changes = Vec::new();
+    for (index, todo) in before.iter().enumerate() {
+        match new.get(&todo.id) {
+            None => changes.push(Change { index, before: Some(todo.clone()), after: None }),
+            Some((_, updated)) if *updated != todo => {
+                changes.push(Change { index, before: Some(todo.clone()), after: Some((*updated).clone()) })
+            }
+            Some(_) => {}
+        }
+    }
+    for (index, todo) in after.iter().enumerate() {
+        if !old.contains_key(&todo.id) {
+            changes.push(Change { index, before: None, after: Some(todo.clone()) });
+        }
+    }
+    changes
+}
@@ -0,0 +1,48 @@
+//! Core todo list engine: the [`Todo`] model and a [`TodoList`] persisted as JSON.
+//!
+//! The `todo` binary is a thin command-line front-end over this crate.
+
+mod backup;
+pub mod config;
+mod dates;
+mod deps;
+mod due;
+mod error;
+pub mod export;
+mod group;
+mod ids;
+mod index;
+mod journal;
+mod list;
+pub mod location;
+pub mod query;
+mod recover;
+mod recur;
+pub mod schema;
+pub mod search;
+mod sort;
+mod storage;
+pub mod theme;
+mod todo;
+mod tree;
+
+pub use backup::{Backup, DEFAULT_BACKUP_LIMIT};
+pub use dates::{format_due_date, local_date, local_due, local_zone, parse_due_date, parse_due_date_from, DueDate};
+pub use config::Config;
+pub use due::{relative_due, DueReport, OVERDUE_EXIT_CODE};
+pub use error::{Error, Result};
+pub use export::Format;
+pub use group::{group_todos, DueBucket, Group, GroupBy};
+pub use ids::parse_ids;
+pub use journal::{Change, Journal, Operation};
+pub use list::TodoList;
+pub use location::Location;
+pub use query::Query;
+pub use recover::Recovery;
+pub use recur::Recurrence;
+pub use search::Search;
+pub use sort::{sort_todos, SortField, SortKey};
+pub use storage::IdRepair;
+pub use theme::Theme;
+pub use todo::{ChecklistItem, Priority, Status, Todo, TodoEdit};
+pub use tree::{tree, ChildPolicy, Node, Overview, Progress};
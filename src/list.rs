@@ -0,0 +1,628 @@
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+use uuid::Uuid;
+
+use crate::backup::{self, Backup, DEFAULT_BACKUP_LIMIT};
+use crate::deps;
+use crate::error::{Error, Result};
+use crate::index::SearchIndex;
+use crate::journal::{Journal, Operation};
+use crate::location;
+use crate::query::Query;
+use crate::recover::{self, Recovery};
+use crate::schema::CURRENT_VERSION;
+use crate::search::{Hit, Search};
+use crate::storage::{IdRepair, Store};
+use crate::todo::{ChecklistItem, Status, Todo, TodoEdit};
+use crate::tree::{self, ChildPolicy, Overview, Progress};
+
+pub struct TodoList {
+    store: Store,
+    file_path: PathBuf,
+    repairs: Vec<IdRepair>,
+    migrated_from: Option<u32>,
+    backup_limit: usize,
+    journal: Journal,
+    spawned: Vec<usize>,
+    /// Refreshed by [`TodoList::find`] as well as [`TodoList::save`], hence the cell.
+    index: RefCell<SearchIndex>,
+}
+
+impl TodoList {
+    /// Opens the data file picked by [`location::resolve`] with no explicit path.
+    pub fn new() -> Result<Self> {
+        Self::open(location::resolve(None).path)
+    }
+
+    /// Opens the list stored at `path`, starting empty if the file does not exist yet.
+    ///
+    /// Files written with an older schema are migrated on load (see
+    /// [`TodoList::migrated_from`]), duplicate ids are reassigned (see
+    /// [`TodoList::repairs`]) and the upgraded file is saved.
+    ///
+    /// A file that fails to parse yields [`Error::Corrupt`]; it is never replaced
+    /// with an empty list.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::open_with_backup_limit(path, DEFAULT_BACKUP_LIMIT)
+    }
+
+    /// Like [`TodoList::open`], keeping `backup_limit` backups (see
+    /// [`TodoList::set_backup_limit`]) from the first save on, including the one
+    /// that stores an upgraded or repaired file.
+    pub fn open_with_backup_limit<P: AsRef<Path>>(path: P, backup_limit: usize) -> Result<Self> {
+        let file_path = path.as_ref().to_path_buf();
+        let (mut store, version) = if file_path.exists() {
+            let content = fs::read_to_string(&file_path)?;
+            match Store::from_json(&content) {
+                Ok(loaded) => loaded,
+                Err(Error::Json(err)) => {
+                    let location = format!(" at line {} column {}", err.line(), err.column());
+                    let message = err.to_string();
+                    return Err(Error::Corrupt {
+                        quarantined: backup::quarantine(&file_path).ok(),
+                        path: file_path,
+                        line: err.line(),
+                        column: err.column(),
+                        message: message.trim_end_matches(&location).to_string(),
+                    })
+                }
+                Err(err) => return Err(err),
+            }
+        } else {
+            (Store::default(), CURRENT_VERSION)
+        };
+
+        let (repairs, changed) = store.repair();
+        let mut list = TodoList::from_store(store, file_path, repairs);
+        list.backup_limit = backup_limit;
+        list.journal = Journal::load(&list.file_path);
+        list.index = RefCell::new(SearchIndex::load(&list.file_path));
+        if version != CURRENT_VERSION {
+            list.migrated_from = Some(version);
+        }
+        if (changed || list.migrated_from.is_some()) && list.file_path.exists() {
+            list.save()?;
+        } else if list.file_path.exists() {
+            list.save_index();
+        }
+        Ok(list)
+    }
+
+    /// Salvages whatever entries can be read from the file at `path`, field by
+    /// field, without writing anything. Call [`TodoList::save_recovered`] on the
+    /// result to replace the damaged file (which is backed up first).
+    ///
+    /// Ids stay unique for good: `next_id` never drops below what the damaged
+    /// file or its newest readable backup had handed out.
+    pub fn recover<P: AsRef<Path>>(path: P) -> Result<(Self, Recovery)> {
+        let file_path = path.as_ref().to_path_buf();
+        let content = String::from_utf8_lossy(&fs::read(&file_path)?).into_owned();
+        let (mut store, recovery) = recover::salvage(&content);
+
+        let newest = backup::list(&file_path)?
+            .into_iter()
+            .find_map(|backup| Store::from_json(&fs::read_to_string(backup.path).ok()?).ok());
+        if let Some((mut backup, _)) = newest {
+            backup.repair();
+            store.next_id = store.next_id.max(backup.next_id);
+        }
+
+        let (repairs, _) = store.repair();
+        Ok((TodoList::from_store(store, file_path, repairs), recovery))
+    }
+
+    /// Saves a list from [`TodoList::recover`] over the damaged file, which is
+    /// backed up first, and clears the undo history since it no longer matches.
+    pub fn save_recovered(&self) -> Result<()> {
+        self.save()?;
+        self.journal.save(&self.file_path)
+    }
+
+    fn from_store(store: Store, file_path: PathBuf, repairs: Vec<IdRepair>) -> Self {
+        TodoList {
+            store,
+            file_path,
+            repairs,
+            migrated_from: None,
+            backup_limit: DEFAULT_BACKUP_LIMIT,
+            journal: Journal::default(),
+            spawned: Vec::new(),
+            index: RefCell::new(SearchIndex::default()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.file_path
+    }
+
+    /// The schema version the file was upgraded from while loading, if it was old.
+    pub fn migrated_from(&self) -> Option<u32> {
+        self.migrated_from
+    }
+
+    /// Ids that were reassigned while loading because they were duplicated.
+    pub fn repairs(&self) -> &[IdRepair] {
+        &self.repairs
+    }
+
+    /// Ids of the occurrences spawned by completing recurring todos in the last
+    /// status change.
+    pub fn spawned(&self) -> &[usize] {
+        &self.spawned
+    }
+
+    pub fn todos(&self) -> &[Todo] {
+        &self.store.todos
+    }
+
+    pub fn get(&self, id: usize) -> Option<&Todo> {
+        self.store.todos.iter().find(|t| t.id == id)
+    }
+
+    /// How many of the direct subtasks of `id` are done, or `None` without subtasks.
+    pub fn progress(&self, id: usize) -> Option<Progress> {
+        Progress::of(&self.store.todos, id)
+    }
+
+    /// Prerequisites of `id` that are still open; see [`Todo::depends_on`].
+    pub fn open_prerequisites(&self, id: usize) -> Vec<usize> {
+        self.get(id).map_or_else(Vec::new, |todo| deps::open_prerequisites(&self.store.todos, todo))
+    }
+
+    /// Progress and prerequisite lookups for every todo at once, for rendering many rows.
+    pub fn overview(&self) -> Overview {
+        Overview::new(&self.store.todos)
+    }
+
+    /// An unblocked todo or one in progress whose prerequisites are all finished.
+    pub fn is_ready(&self, todo: &Todo) -> bool {
+        matches!(todo.status, Status::Todo | Status::InProgress)
+            && deps::open_prerequisites(&self.store.todos, todo).is_empty()
+    }
+
+    pub fn get_by_uuid(&self, uuid: Uuid) -> Option<&Todo> {
+        self.store.todos.iter().find(|t| t.uuid == Some(uuid))
+    }
+
+    /// Sets how many timestamped backups are kept; `0` disables backups.
+    pub fn set_backup_limit(&mut self, limit: usize) {
+        self.backup_limit = limit;
+    }
+
+    /// Backs up the current file, then atomically replaces it with the in-memory list.
+    /// Missing parent directories are created. The search index next to the file
+    /// is brought up to date as well.
+    pub fn save(&self) -> Result<()> {
+        let content = serde_json::to_string_pretty(&self.store)?;
+        if let Some(dir) = self.file_path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        backup::rotate(&self.file_path, self.backup_limit)?;
+        backup::write_atomic(&self.file_path, content.as_bytes())?;
+        self.save_index();
+        Ok(())
+    }
+
+    /// Re-indexes todos changed since the index was written and stores it if
+    /// anything changed. The index is only a cache, so failing to write it is
+    /// not an error; it is rebuilt on the next open.
+    fn save_index(&self) {
+        let mut index = self.index.borrow_mut();
+        if index.update(&self.store.todos) || !index.exists(&self.file_path) {
+            index.save(&self.file_path).ok();
+        }
+    }
+
+    /// Available backups, newest first.
+    pub fn backups(&self) -> Result<Vec<Backup>> {
+        Self::backups_of(&self.file_path)
+    }
+
+    /// Backups of the data file at `path`, newest first. Works even when the
+    /// file itself is corrupt.
+    pub fn backups_of<P: AsRef<Path>>(path: P) -> Result<Vec<Backup>> {
+        Ok(backup::list(path.as_ref())?)
+    }
+
+    /// Replaces the data file at `path` with its backup at `index` (0 is the
+    /// newest). The contents being replaced, corrupt or not, are backed up first,
+    /// keeping `backup_limit` backups, and the undo history is cleared since it
+    /// no longer matches the list.
+    pub fn restore<P: AsRef<Path>>(path: P, index: usize, backup_limit: usize) -> Result<(Self, Backup)> {
+        let file_path = path.as_ref().to_path_buf();
+        let backup = Self::backups_of(&file_path)?
+            .into_iter()
+            .nth(index)
+            .ok_or(Error::BackupNotFound(index))?;
+        let content = fs::read_to_string(&backup.path)?;
+        let (mut store, _) = Store::from_json(&content)?;
+
+        let (repairs, _) = store.repair();
+        let mut list = TodoList::from_store(store, file_path, repairs);
+        list.backup_limit = backup_limit;
+        list.save()?;
+        list.journal.save(&list.file_path)?;
+        Ok((list, backup))
+    }
+
+    /// Adds a todo with the next unused id; ids of deleted todos are never handed out again.
+    pub fn add(&mut self, title: String, priority: Option<String>, due: Option<String>, categories: Vec<String>) -> Result<&Todo> {
+        self.add_todo(Todo::new(title, priority, due, categories)?)
+    }
+
+    /// Adds a todo built by the caller, e.g. with [`Todo::new`] plus a
+    /// recurrence or parent, giving it the next free id. The parent must exist.
+    pub fn add_todo(&mut self, mut todo: Todo) -> Result<&Todo> {
+        if let Some(parent) = todo.parent_id {
+            self.index_of(parent)?;
+        }
+        let before = self.store.todos.clone();
+        todo.id = self.store.allocate_id();
+        let description = format!("add #{} \"{}\"", todo.id, todo.title);
+        self.store.todos.push(todo);
+        self.commit(description, before)?;
+        Ok(self.store.todos.last().unwrap())
+    }
+
+    /// Returns the todos matching `query`, in list order.
+    pub fn filter(&self, query: &Query) -> Vec<&Todo> {
+        self.store.todos.iter().filter(|todo| query.matches(todo)).collect()
+    }
+
+    /// Resolves a bulk selection to ids: the given `ids` (all of which must
+    /// exist), or every todo when `ids` is `None`, narrowed down by `query`.
+    pub fn select(&self, ids: Option<&[usize]>, query: Option<&Query>) -> Result<Vec<usize>> {
+        let todos: Vec<&Todo> = match ids {
+            Some(ids) => ids.iter().map(|&id| self.get(id).ok_or(Error::NotFound(id))).collect::<Result<_>>()?,
+            None => self.store.todos.iter().collect(),
+        };
+        Ok(todos
+            .into_iter()
+            .filter(|todo| query.is_none_or(|query| query.matches(todo)))
+            .map(|todo| todo.id)
+            .collect())
+    }
+
+    /// Typo-tolerant search over titles, tags and notes, best match first; see
+    /// [`Search::fuzzy`].
+    pub fn search(&self, query: &str) -> Vec<&Todo> {
+        self.find(&Search::fuzzy(query)).into_iter().map(|hit| hit.todo).collect()
+    }
+
+    /// Runs `search` over every todo, returning scored hits with their match spans.
+    /// Fuzzy searches only score the todos the search index picks out; the hits
+    /// are the same as [`Search::run`] over the whole list.
+    pub fn find(&self, search: &Search) -> Vec<Hit<'_>> {
+        let mut index = self.index.borrow_mut();
+        index.update(&self.store.todos);
+        match index.candidates(search) {
+            Some(ids) => search.run_over(self.store.todos.iter().filter(|todo| ids.contains(&todo.id))),
+            None => search.run(&self.store.todos),
+        }
+    }
+
+    /// Marks the todo done and records when.
+    pub fn complete(&mut self, id: usize) -> Result<&Todo> {
+        self.set_status(id, Status::Done, ChildPolicy::Refuse)
+    }
+
+    pub fn start(&mut self, id: usize) -> Result<&Todo> {
+        self.set_status(id, Status::InProgress, ChildPolicy::Refuse)
+    }
+
+    pub fn block(&mut self, id: usize) -> Result<&Todo> {
+        self.set_status(id, Status::Blocked, ChildPolicy::Refuse)
+    }
+
+    /// Puts a done, cancelled or started todo back to `Todo`, clearing `completed_at`.
+    pub fn reopen(&mut self, id: usize) -> Result<&Todo> {
+        self.set_status(id, Status::Todo, ChildPolicy::Refuse)
+    }
+
+    pub fn cancel(&mut self, id: usize) -> Result<&Todo> {
+        self.set_status(id, Status::Cancelled, ChildPolicy::Refuse)
+    }
+
+    /// Moves the todo with `id` to `status`; see [`Todo::set_status`] and
+    /// [`TodoList::set_status_many`].
+    pub fn set_status(&mut self, id: usize, status: Status, children: ChildPolicy) -> Result<&Todo> {
+        let index = self.index_of(id)?;
+        if self.store.todos[index].status == status {
+            return Err(Error::AlreadyInStatus(id, status));
+        }
+        self.set_status_many(&[id], status, children)?;
+        Ok(&self.store.todos[index])
+    }
+
+    /// Moves every todo in `ids` to `status` as a single undoable operation and
+    /// returns the ids that changed; todos already in `status` are skipped.
+    /// Nothing changes if any id does not exist.
+    ///
+    /// Completing or cancelling a todo with open subtasks follows `children`:
+    /// the subtasks get the same status, are detached, or nothing changes and
+    /// [`Error::HasSubtasks`] is returned.
+    ///
+    /// Completing a recurring todo appends its next occurrence, which inherits
+    /// the recurrence; see [`TodoList::spawned`].
+    pub fn set_status_many(&mut self, ids: &[usize], status: Status, children: ChildPolicy) -> Result<Vec<usize>> {
+        let mut ids = ids.to_vec();
+        let mut orphans = Vec::new();
+        if !status.is_open() {
+            for id in ids.clone() {
+                let open: Vec<usize> = tree::descendants(&self.store.todos, id)
+                    .into_iter()
+                    .filter(|child| !ids.contains(child) && self.get(*child).is_some_and(|todo| todo.status.is_open()))
+                    .collect();
+                match children {
+                    _ if open.is_empty() => {}
+                    ChildPolicy::Cascade => ids.extend(open),
+                    ChildPolicy::Refuse => return Err(Error::HasSubtasks(id, open)),
+                    ChildPolicy::Orphan => {
+                        let direct = open.into_iter().filter(|&child| self.get(child).is_some_and(|todo| todo.parent_id == Some(id)));
+                        orphans.extend(direct);
+                    }
+                }
+            }
+        }
+
+        let indices = ids.iter().map(|&id| self.index_of(id)).collect::<Result<Vec<_>>>()?;
+        let before = self.store.todos.clone();
+        for todo in self.store.todos.iter_mut().filter(|todo| orphans.contains(&todo.id)) {
+            todo.parent_id = None;
+        }
+        let mut changed = Vec::new();
+        let mut occurrences = Vec::new();
+        for index in indices {
+            let todo = &mut self.store.todos[index];
+            if todo.status != status {
+                todo.set_status(status);
+                changed.push(todo.id);
+                if let Some(completed_at) = todo.completed_at {
+                    match todo.next_occurrence(completed_at) {
+                        Ok(next) => occurrences.extend(next),
+                        Err(err) => {
+                            self.store.todos = before;
+                            return Err(err);
+                        }
+                    }
+                    todo.recurrence = None;
+                }
+            }
+        }
+
+        self.spawned.clear();
+        for mut occurrence in occurrences {
+            occurrence.id = self.store.allocate_id();
+            self.spawned.push(occurrence.id);
+            self.store.todos.push(occurrence);
+        }
+
+        if !changed.is_empty() {
+            let verb = match status {
+                Status::Todo => "reopen",
+                Status::InProgress => "start",
+                Status::Blocked => "block",
+                Status::Done => "complete",
+                Status::Cancelled => "cancel",
+            };
+            self.commit(describe(verb, &changed), before)?;
+        }
+        Ok(changed)
+    }
+
+    /// Applies `edit` to the todo with `id`, keeping its id, creation time and status.
+    /// A new parent must exist and must not be the todo itself or one of its subtasks.
+    pub fn edit(&mut self, id: usize, edit: TodoEdit) -> Result<&Todo> {
+        let index = self.index_of(id)?;
+        if edit.title.as_deref().is_some_and(|title| title.trim().is_empty()) {
+            return Err(Error::Invalid("Title cannot be empty".to_string()));
+        }
+        if let Some(Some(parent)) = edit.parent_id {
+            self.index_of(parent)?;
+            if parent == id {
+                return Err(Error::Invalid(format!("Task {} cannot be its own parent", id)));
+            }
+            if tree::descendants(&self.store.todos, id).contains(&parent) {
+                return Err(Error::Invalid(format!("Task {} cannot be moved under its own subtask {}", id, parent)));
+            }
+        }
+        let before = self.store.todos.clone();
+        self.store.todos[index].apply(edit);
+
+        self.commit(format!("edit #{}", id), before)?;
+        Ok(&self.store.todos[index])
+    }
+
+    /// Appends a timestamped paragraph to the notes of `id`; see [`Todo::append_note`].
+    pub fn note(&mut self, id: usize, text: &str) -> Result<&Todo> {
+        if text.trim().is_empty() {
+            return Err(Error::Invalid("Note cannot be empty".to_string()));
+        }
+        self.update(id, format!("note #{}", id), |todo| {
+            todo.append_note(text, Local::now());
+            Ok(())
+        })
+    }
+
+    /// Adds an unticked item to the end of the checklist of `id`.
+    pub fn add_checklist_item(&mut self, id: usize, text: &str) -> Result<&Todo> {
+        if text.trim().is_empty() {
+            return Err(Error::Invalid("Checklist item cannot be empty".to_string()));
+        }
+        self.update(id, format!("checklist #{}", id), |todo| {
+            todo.checklist.push(ChecklistItem { text: text.trim().to_string(), done: false });
+            Ok(())
+        })
+    }
+
+    /// Ticks off or unticks the checklist item at `index` (0-based) of `id`.
+    pub fn set_checklist_item(&mut self, id: usize, index: usize, done: bool) -> Result<&Todo> {
+        self.update(id, format!("checklist #{}", id), |todo| {
+            checklist_item(todo, index)?.done = done;
+            Ok(())
+        })
+    }
+
+    /// Removes the checklist item at `index` (0-based) of `id`.
+    pub fn remove_checklist_item(&mut self, id: usize, index: usize) -> Result<&Todo> {
+        self.update(id, format!("checklist #{}", id), |todo| {
+            checklist_item(todo, index)?;
+            todo.checklist.remove(index);
+            Ok(())
+        })
+    }
+
+    /// Makes `id` wait for each of `prerequisites`. Fails with
+    /// [`Error::DependencyCycle`] if a prerequisite already waits on `id`,
+    /// directly or through other tasks.
+    pub fn depend(&mut self, id: usize, prerequisites: &[usize]) -> Result<&Todo> {
+        let index = self.index_of(id)?;
+        for &prerequisite in prerequisites {
+            self.index_of(prerequisite)?;
+            if let Some(path) = deps::path(&self.store.todos, prerequisite, id) {
+                return Err(Error::DependencyCycle([vec![id], path].concat()));
+            }
+        }
+
+        let before = self.store.todos.clone();
+        let todo = &mut self.store.todos[index];
+        for &prerequisite in prerequisites {
+            if !todo.depends_on.contains(&prerequisite) {
+                todo.depends_on.push(prerequisite);
+            }
+        }
+        if self.store.todos[index] != before[index] {
+            let on: Vec<String> = prerequisites.iter().map(|prerequisite| format!("#{}", prerequisite)).collect();
+            self.commit(format!("depend #{} on {}", id, on.join(", ")), before)?;
+        }
+        Ok(&self.store.todos[index])
+    }
+
+    /// Removes `prerequisites` from the dependencies of `id`.
+    pub fn undepend(&mut self, id: usize, prerequisites: &[usize]) -> Result<&Todo> {
+        let index = self.index_of(id)?;
+        let before = self.store.todos.clone();
+        self.store.todos[index].depends_on.retain(|prerequisite| !prerequisites.contains(prerequisite));
+        if self.store.todos[index] != before[index] {
+            self.commit(format!("undepend #{}", id), before)?;
+        }
+        Ok(&self.store.todos[index])
+    }
+
+    /// Deletes a todo without subtasks; see [`TodoList::delete_many`].
+    pub fn delete(&mut self, id: usize) -> Result<Todo> {
+        let mut deleted = self.delete_many(&[id], ChildPolicy::Refuse)?;
+        Ok(deleted.remove(0))
+    }
+
+    /// Removes every todo in `ids` as a single undoable operation and returns
+    /// them. Subtasks of a deleted todo are deleted too, detached, or keep
+    /// anything from changing, according to `children`. Nothing changes if any
+    /// id does not exist.
+    pub fn delete_many(&mut self, ids: &[usize], children: ChildPolicy) -> Result<Vec<Todo>> {
+        for &id in ids {
+            self.index_of(id)?;
+        }
+        let mut ids = ids.to_vec();
+        for id in ids.clone() {
+            let below: Vec<usize> =
+                tree::descendants(&self.store.todos, id).into_iter().filter(|child| !ids.contains(child)).collect();
+            match children {
+                _ if below.is_empty() => {}
+                ChildPolicy::Cascade => ids.extend(below),
+                ChildPolicy::Refuse => return Err(Error::HasSubtasks(id, below)),
+                ChildPolicy::Orphan => {}
+            }
+        }
+
+        let before = self.store.todos.clone();
+        let (deleted, mut kept): (Vec<Todo>, Vec<Todo>) = before.iter().cloned().partition(|todo| ids.contains(&todo.id));
+        for todo in kept.iter_mut() {
+            if todo.parent_id.is_some_and(|parent| ids.contains(&parent)) {
+                todo.parent_id = None;
+            }
+            todo.depends_on.retain(|prerequisite| !ids.contains(prerequisite));
+        }
+        self.store.todos = kept;
+
+        let description = match &deleted[..] {
+            [todo] => format!("delete #{} \"{}\"", todo.id, todo.title),
+            _ => describe("delete", &ids),
+        };
+        self.commit(description, before)?;
+        Ok(deleted)
+    }
+
+    /// Reverts the most recent operation and returns it.
+    pub fn undo(&mut self) -> Result<&Operation> {
+        if self.journal.undo(&mut self.store.todos).is_none() {
+            return Err(Error::NothingToUndo);
+        }
+        self.save()?;
+        self.journal.save(&self.file_path)?;
+        Ok(&self.journal.operations[self.journal.position])
+    }
+
+    /// Re-applies the most recently undone operation and returns it.
+    pub fn redo(&mut self) -> Result<&Operation> {
+        if self.journal.redo(&mut self.store.todos).is_none() {
+            return Err(Error::NothingToRedo);
+        }
+        self.save()?;
+        self.journal.save(&self.file_path)?;
+        Ok(&self.journal.operations[self.journal.position - 1])
+    }
+
+    /// The undo/redo history, oldest operation first.
+    pub fn history(&self) -> &Journal {
+        &self.journal
+    }
+
+    /// Journals the difference between `before` and the current list, then saves both.
+    fn commit(&mut self, description: String, before: Vec<Todo>) -> Result<()> {
+        self.journal.record(description, &before, &self.store.todos);
+        self.save()?;
+        self.journal.save(&self.file_path)
+    }
+
+    /// Runs `change` on the todo with `id` and records it as one operation if
+    /// anything changed. Nothing is saved when `change` fails.
+    fn update(&mut self, id: usize, description: String, change: impl FnOnce(&mut Todo) -> Result<()>) -> Result<&Todo> {
+        let index = self.index_of(id)?;
+        let before = self.store.todos.clone();
+        if let Err(err) = change(&mut self.store.todos[index]) {
+            self.store.todos = before;
+            return Err(err);
+        }
+        if self.store.todos[index] != before[index] {
+            self.commit(description, before)?;
+        }
+        Ok(&self.store.todos[index])
+    }
+
+    fn index_of(&self, id: usize) -> Result<usize> {
+        self.store.todos
+            .iter()
+            .position(|t| t.id == id)
+            .ok_or(Error::NotFound(id))
+    }
+}
+
+fn checklist_item(todo: &mut Todo, index: usize) -> Result<&mut ChecklistItem> {
+    let id = todo.id;
+    todo.checklist
+        .get_mut(index)
+        .ok_or_else(|| Error::Invalid(format!("Task {} has no checklist item {}", id, index + 1)))
+}
+
+/// Journal description for an operation on several todos, e.g. `complete #1, #4`.
+fn describe(verb: &str, ids: &[usize]) -> String {
+    if ids.len() > 5 {
+        return format!("{} {} tasks", verb, ids.len());
+    }
+    let ids: Vec<String> = ids.iter().map(|id| format!("#{}", id)).collect();
+    format!("{} {}", verb, ids.join(", "))
+}
@@ -0,0 +1,89 @@
+use std::env;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Environment variable naming the data file, overridden only by an explicit path.
+pub const FILE_ENV: &str = "TODO_FILE";
+
+/// Name of a project-local list, discovered by walking up from the current directory.
+pub const PROJECT_FILE: &str = ".todo.json";
+
+/// Name of the data file inside the user's data directory.
+pub const DEFAULT_FILE: &str = "todos.json";
+
+/// Where a resolved data file path came from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source {
+    /// Passed explicitly, e.g. with `--file`.
+    Explicit,
+    /// Taken from the `TODO_FILE` environment variable.
+    Env,
+    /// A `.todo.json` in the current directory or one of its parents.
+    Project,
+    /// A `todos.json` in the current directory, where versions before the data
+    /// directory kept the list. Only used while the data directory has none.
+    Legacy,
+    /// The per-user default in the XDG data directory.
+    DataDir,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Source::Explicit => "--file",
+            Source::Env => FILE_ENV,
+            Source::Project => "project",
+            Source::Legacy => "legacy ./todos.json",
+            Source::DataDir => "data dir",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Location {
+    pub path: PathBuf,
+    pub source: Source,
+}
+
+/// Picks the data file, in order of precedence: `explicit`, `$TODO_FILE`, the
+/// nearest `.todo.json` above the current directory, then
+/// `$XDG_DATA_HOME/todo/todos.json` (or the platform equivalent). While that
+/// file does not exist yet, a `todos.json` in the current directory left by
+/// an older version is used instead.
+pub fn resolve(explicit: Option<&Path>) -> Location {
+    if let Some(path) = explicit {
+        return Location { path: path.to_path_buf(), source: Source::Explicit };
+    }
+
+    if let Some(path) = env::var_os(FILE_ENV).filter(|value| !value.is_empty()) {
+        return Location { path: PathBuf::from(path), source: Source::Env };
+    }
+
+    if let Some(path) = env::current_dir().ok().and_then(|dir| find_project_file(&dir)) {
+        return Location { path, source: Source::Project };
+    }
+
+    let path = data_dir_file();
+    let legacy = PathBuf::from(DEFAULT_FILE);
+    if !path.exists() && legacy.is_file() {
+        return Location { path: legacy, source: Source::Legacy };
+    }
+    Location { path, source: Source::DataDir }
+}
+
+/// `todos.json` in the user data directory, where the list lives by default.
+pub fn data_dir_file() -> PathBuf {
+    match dirs::data_dir() {
+        Some(dir) => dir.join("todo").join(DEFAULT_FILE),
+        None => PathBuf::from(DEFAULT_FILE),
+    }
+}
+
+/// Returns the first `.todo.json` found in `start` or any of its ancestors.
+pub fn find_project_file(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_FILE))
+        .find(|candidate| candidate.is_file())
+}
@@ -1,288 +1,1049 @@
-use serde::{Deserialize, Serialize};
-use std::fs;
-use std::io;
-use chrono::{DateTime, Local, NaiveDateTime};
-use std::path::PathBuf;
+use chrono::Local;
 use structopt::StructOpt;
 use colored::*;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process;
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
-enum Priority {
-    High,
-    Medium,
-    Low,
-}
+use todo_list::export::Record;
+use todo_list::config::{self, Config};
+use todo_list::location::{self, Location, Source};
+use todo_list::query;
+use todo_list::schema;
+use todo_list::search::{Field, Hit};
+use todo_list::theme::{ColorChoice, Symbols};
+use todo_list::{
+    format_due_date, group_todos, relative_due, local_zone, parse_due_date, parse_ids, sort_todos, tree, ChildPolicy, DueReport, Error, Format,
+    GroupBy, Overview, Priority, Progress, Query, Recurrence, Result, Search, SortKey, Status, Theme, Todo, TodoEdit,
+    TodoList,
+};
 
-#[derive(Debug, Serialize, Deserialize)]
-struct Todo {
-    id: usize,
-    title: String,
-    completed: bool,
-    created_at: DateTime<Local>,
-    completed_at: Option<DateTime<Local>>,
-    priority: Priority,
-    due_date: Option<NaiveDateTime>,
-    categories: Vec<String>,
-}
+mod interactive;
+mod show;
+mod style;
+
+use style::{colors, symbols, theme};
 
 #[derive(Debug, StructOpt)]
 #[structopt(name = "todo", about = "A feature-rich todo list manager")]
-enum Cli {
+struct Cli {
+    #[structopt(
+        long = "file",
+        global = true,
+        parse(from_os_str),
+        help = "Data file to use (default: $TODO_FILE, nearest .todo.json, then the user data dir)"
+    )]
+    file: Option<PathBuf>,
+    #[structopt(
+        long = "format",
+        global = true,
+        help = "Output of the commands that print tasks (list, search, due and show; not history or restore): text, json, ndjson, csv, tsv, or a template like '{id}\\t{title}\\t{due}'"
+    )]
+    format: Option<String>,
+    #[structopt(
+        long = "color",
+        global = true,
+        default_value = "auto",
+        parse(try_from_str = ColorChoice::from_name),
+        help = "Color output: auto (honours NO_COLOR, CLICOLOR_FORCE and whether stdout is a terminal), always or never"
+    )]
+    color: ColorChoice,
+    #[structopt(
+        long = "theme",
+        global = true,
+        possible_values = todo_list::theme::BUNDLED,
+        help = "Use a bundled theme instead of the one set up in the config file"
+    )]
+    theme: Option<String>,
+    #[structopt(long = "ascii", global = true, help = "Draw with plain ASCII instead of Unicode symbols and emoji")]
+    ascii: bool,
+    #[structopt(long = "no-banner", global = true, help = "Don't print the banner, which is only shown on terminals anyway")]
+    no_banner: bool,
+    #[structopt(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
     #[structopt(name = "add")]
     Add {
         #[structopt(help = "The todo item to add")]
         title: String,
         #[structopt(long = "priority", help = "Priority level (high/medium/low)")]
         priority: Option<String>,
-        #[structopt(long = "due", help = "Due date (YYYY-MM-DD)")]
+        #[structopt(long = "due", help = "Due date: YYYY-MM-DD, today, tomorrow, friday, next week, in 3 days, eom; optionally with a time like 14:00")]
         due: Option<String>,
         #[structopt(long = "tag", help = "Categories (can be used multiple times)", multiple = true)]
         tags: Vec<String>,
+        #[structopt(long = "parent", help = "Add as a subtask of this task id")]
+        parent: Option<usize>,
+        #[structopt(long = "repeat", help = "Recurrence: daily, weekly on mon,thu, monthly on 15, every 3 days, every 10 days after done, or an RRULE")]
+        repeat: Option<String>,
+    },
+    #[structopt(name = "edit", about = "Change an existing task")]
+    Edit {
+        id: usize,
+        #[structopt(long = "title", help = "New title")]
+        title: Option<String>,
+        #[structopt(long = "priority", help = "New priority level (high/medium/low)")]
+        priority: Option<String>,
+        #[structopt(long = "due", help = "New due date (same forms as `add --due`)", conflicts_with = "clear-due")]
+        due: Option<String>,
+        #[structopt(long = "clear-due", help = "Remove the due date")]
+        clear_due: bool,
+        #[structopt(long = "add-tag", help = "Categories to add (can be used multiple times)", multiple = true)]
+        add_tags: Vec<String>,
+        #[structopt(long = "remove-tag", help = "Categories to remove (can be used multiple times)", multiple = true)]
+        remove_tags: Vec<String>,
+        #[structopt(long = "repeat", help = "New recurrence (same forms as `add --repeat`)", conflicts_with = "no-repeat")]
+        repeat: Option<String>,
+        #[structopt(long = "no-repeat", help = "Stop the task from recurring")]
+        no_repeat: bool,
+        #[structopt(long = "parent", help = "Move under this task as a subtask", conflicts_with = "no-parent")]
+        parent: Option<usize>,
+        #[structopt(long = "no-parent", help = "Make the task top-level again")]
+        no_parent: bool,
+        #[structopt(
+            short = "i",
+            long = "interactive",
+            help = "Edit the task in $EDITOR",
+            conflicts_with_all = &["title", "priority", "due", "clear-due", "add-tags", "remove-tags", "repeat", "no-repeat", "parent", "no-parent"]
+        )]
+        interactive: bool,
     },
     #[structopt(name = "list")]
     List {
-        #[structopt(long = "completed", help = "Show only completed items")]
-        completed: bool,
-        #[structopt(long = "priority", help = "Filter by priority")]
-        priority: Option<String>,
-        #[structopt(long = "tag", help = "Filter by category")]
-        tag: Option<String>,
+        #[structopt(flatten)]
+        filter: ListFilter,
+        #[structopt(
+            long = "sort",
+            allow_hyphen_values = true,
+            help = "Sort keys: id, title, priority, due, created, completed; comma-separated, prefix - for descending (e.g. priority,-due)"
+        )]
+        sort: Option<String>,
+        #[structopt(long = "group-by", help = "Group by priority, tag, due or status")]
+        group_by: Option<String>,
     },
     #[structopt(name = "search")]
     Search {
+        #[structopt(help = "Words to look for in titles, tags and notes (typos are tolerated), or a pattern with --regex")]
         query: String,
+        #[structopt(long = "regex", help = "Treat the query as a case-insensitive regular expression")]
+        regex: bool,
+        #[structopt(long = "limit", help = "Show at most this many results")]
+        limit: Option<usize>,
+        #[structopt(long = "where", help = "Only results matching this filter expression")]
+        filter: Option<String>,
     },
+    #[structopt(name = "complete", about = "Mark tasks as done")]
     Complete {
-        id: usize,
+        #[structopt(flatten)]
+        select: Selector,
+    },
+    #[structopt(name = "start", about = "Mark tasks as in progress")]
+    Start {
+        #[structopt(flatten)]
+        select: Selector,
     },
+    #[structopt(name = "block", about = "Mark tasks as blocked")]
+    Block {
+        #[structopt(flatten)]
+        select: Selector,
+    },
+    #[structopt(name = "reopen", about = "Move done, cancelled or started tasks back to todo")]
+    Reopen {
+        #[structopt(flatten)]
+        select: Selector,
+    },
+    #[structopt(name = "cancel", about = "Cancel tasks without completing them")]
+    Cancel {
+        #[structopt(flatten)]
+        select: Selector,
+    },
+    #[structopt(name = "delete", about = "Delete tasks")]
     Delete {
+        #[structopt(flatten)]
+        select: Selector,
+    },
+    #[structopt(name = "show", about = "Show everything about a task, including notes and checklist")]
+    Show { id: usize },
+    #[structopt(name = "note", about = "Append a timestamped note to a task")]
+    Note {
         id: usize,
+        #[structopt(help = "Note text (Markdown); read from stdin when left out")]
+        text: Option<String>,
+    },
+    #[structopt(name = "check", about = "Add, tick off or remove checklist items of a task")]
+    Check {
+        id: usize,
+        #[structopt(long = "add", help = "New checklist item (can be used multiple times)", multiple = true, number_of_values = 1)]
+        add: Vec<String>,
+        #[structopt(long = "done", help = "Tick off item number N (as shown by `todo show`)")]
+        done: Option<usize>,
+        #[structopt(long = "undone", help = "Untick item number N")]
+        undone: Option<usize>,
+        #[structopt(long = "remove", help = "Remove item number N")]
+        remove: Option<usize>,
+    },
+    #[structopt(name = "depend", about = "Make a task wait until other tasks are done")]
+    Depend {
+        id: usize,
+        #[structopt(long = "on", help = "Prerequisite task ids, e.g. 3 or 1,4,7-10")]
+        on: String,
+        #[structopt(long = "remove", help = "Drop these prerequisites instead of adding them")]
+        remove: bool,
+    },
+    #[structopt(name = "due", about = "Show open tasks that are overdue, due today or due soon")]
+    Due {
+        #[structopt(long = "days", default_value = "7", help = "How many days ahead count as upcoming")]
+        days: i64,
+        #[structopt(long = "exit-code", help = "Exit with status 2 if any task is overdue (for scripts)")]
+        exit_code: bool,
+    },
+    #[structopt(name = "undo", about = "Revert the last change")]
+    Undo,
+    #[structopt(name = "redo", about = "Re-apply the last undone change")]
+    Redo,
+    #[structopt(name = "history", about = "List changes that can be undone or redone")]
+    History,
+    #[structopt(name = "path", about = "Show which data file is in use and why")]
+    Path,
+    #[structopt(name = "doctor", about = "Check the data file and recover what can be salvaged")]
+    Doctor {
+        #[structopt(long = "dry-run", help = "Report what would be recovered without writing")]
+        dry_run: bool,
+    },
+    #[structopt(name = "restore", about = "List backups, or roll back to one of them")]
+    Restore {
+        #[structopt(help = "Backup number as shown by `todo restore` (1 is the newest)")]
+        number: Option<usize>,
     },
 }
-impl Todo {
-    fn new(title: String, priority_str: Option<String>, due_date_str: Option<String>, categories: Vec<String>) -> Self {
-        let priority = match priority_str.as_deref() {
-            Some("high") => Priority::High,
-            Some("medium") => Priority::Medium,
-            _ => Priority::Low,
+
+/// Which tasks `list` shows.
+#[derive(Debug, StructOpt)]
+struct ListFilter {
+    #[structopt(help = "Filter expression, e.g. \"priority:high and (tag:work or tag:urgent) and not done\"")]
+    query: Option<String>,
+    #[structopt(long = "all", help = "Show tasks in every status", conflicts_with_all = &["open", "done", "status"])]
+    all: bool,
+    #[structopt(long = "open", help = "Show only open tasks (the default)", conflicts_with_all = &["done", "status"])]
+    open: bool,
+    #[structopt(long = "done", alias = "completed", help = "Show only completed tasks", conflicts_with = "status")]
+    done: bool,
+    #[structopt(
+        long = "status",
+        help = "Show items with this status (todo/in-progress/blocked/done/cancelled, can be used multiple times)",
+        multiple = true
+    )]
+    status: Vec<String>,
+    #[structopt(long = "ready", help = "Only tasks that can be worked on now: not blocked and with every prerequisite done")]
+    ready: bool,
+    #[structopt(long = "priority", help = "Filter by priority")]
+    priority: Option<String>,
+    #[structopt(long = "tag", help = "Filter by category")]
+    tag: Option<String>,
+    #[structopt(long = "since", help = "Only tasks completed on or after this date (YYYY-MM-DD or today); open tasks still show")]
+    since: Option<String>,
+    #[structopt(long = "until", help = "Only tasks completed on or before this date (YYYY-MM-DD or today); open tasks still show")]
+    until: Option<String>,
+}
+
+impl ListFilter {
+    /// Parses the flags and combines them into one query; see [`query::ListFilter::to_query`].
+    fn to_query(&self) -> Result<Query> {
+        let filter = query::ListFilter {
+            query: self.query.as_deref().map(Query::parse).transpose()?,
+            all: self.all,
+            open: self.open,
+            done: self.done,
+            statuses: self.status.iter().map(|name| parse_status(name)).collect::<Result<_>>()?,
+            priority: self.priority.as_deref().map(parse_priority).transpose()?,
+            tag: self.tag.clone(),
+            since: self.since.as_deref().map(query::parse_date).transpose()?,
+            until: self.until.as_deref().map(query::parse_date).transpose()?,
         };
+        Ok(filter.to_query())
+    }
+}
+
+/// Which tasks a mutating command applies to.
+#[derive(Debug, StructOpt)]
+struct Selector {
+    #[structopt(help = "Task ids, lists and ranges, e.g. 3 or 1,4,7-10")]
+    ids: Option<String>,
+    #[structopt(long = "where", help = "Only tasks matching this filter expression, e.g. \"tag:work and priority:low\"")]
+    filter: Option<String>,
+    #[structopt(long = "dry-run", help = "Show which tasks would change without changing them")]
+    dry_run: bool,
+    #[structopt(short = "y", long = "yes", help = "Don't ask for confirmation on large changes")]
+    yes: bool,
+    #[structopt(
+        long = "children",
+        default_value = "refuse",
+        help = "What completing, cancelling or deleting a task does to its subtasks: refuse, cascade or orphan"
+    )]
+    children: String,
+}
 
-        let due_date = due_date_str.and_then(|date_str| {
-            NaiveDateTime::parse_from_str(&format!("{} 23:59:59", date_str), "%Y-%m-%d %H:%M:%S").ok()
-        });
-
-        Todo {
-            id: 0, // Will be set when adding to list
-            title,
-            completed: false,
-            created_at: Local::now(),
-            completed_at: None,
-            priority,
-            due_date,
-            categories,
+/// Bulk changes touching more tasks than this ask for confirmation first.
+const CONFIRM_THRESHOLD: usize = 5;
+
+enum Action {
+    SetStatus(Status),
+    Delete,
+}
+
+impl Action {
+    fn verb(&self) -> &'static str {
+        match self {
+            Action::SetStatus(Status::Done) => "complete",
+            Action::SetStatus(Status::InProgress) => "start",
+            Action::SetStatus(Status::Blocked) => "block",
+            Action::SetStatus(Status::Todo) => "reopen",
+            Action::SetStatus(Status::Cancelled) => "cancel",
+            Action::Delete => "delete",
         }
     }
 
-    fn format_priority(&self) -> ColoredString {
-        match self.priority {
-            Priority::High => "⚠ HIGH".red(),
-            Priority::Medium => "◆ MED".yellow(),
-            Priority::Low => "○ LOW".green(),
+    fn done_message(&self) -> ColoredString {
+        match self {
+            Action::SetStatus(Status::Done) => colors().done.paint(&format!("{} Completed:", symbols().done)),
+            Action::SetStatus(Status::InProgress) => colors().in_progress.paint(&format!("{} Started:", symbols().in_progress)),
+            Action::SetStatus(Status::Blocked) => colors().blocked.paint(&format!("{} Blocked:", symbols().blocked)),
+            Action::SetStatus(Status::Todo) => colors().todo.paint(&format!("{} Reopened:", symbols().todo)),
+            Action::SetStatus(Status::Cancelled) => colors().cancelled.paint(&format!("{} Cancelled:", symbols().cancelled)),
+            Action::Delete => colors().error.paint(&format!("{} Deleted:", symbols().error)),
         }
     }
 }
 
-struct TodoList {
-    todos: Vec<Todo>,
-    file_path: PathBuf,
+/// Ids of every todo, which id ranges pick from.
+fn ids_in_use(todo_list: &TodoList) -> Vec<usize> {
+    todo_list.todos().iter().map(|todo| todo.id).collect()
 }
 
-impl TodoList {
-    fn new() -> io::Result<Self> {
-        let file_path = PathBuf::from("todos.json");
-        let todos = if file_path.exists() {
-            let content = fs::read_to_string(&file_path)?;
-            serde_json::from_str(&content).unwrap_or_default()
-        } else {
-            Vec::new()
-        };
-        
-        Ok(TodoList { todos, file_path })
-    }
+fn confirm(prompt: &str) -> bool {
+    print!("{} {} [y/N] ", colors().warn.paint("?"), prompt);
+    let _ = io::stdout().flush();
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).is_ok() && matches!(answer.trim(), "y" | "Y" | "yes")
+}
 
-    fn save(&self) -> io::Result<()> {
-        let content = serde_json::to_string_pretty(&self.todos)?;
-        fs::write(&self.file_path, content)
+/// Runs `action` on the tasks picked by `select`, previewing or confirming as asked.
+fn run_bulk(todo_list: &mut TodoList, select: Selector, action: Action) -> Result<()> {
+    let ids = select.ids.as_deref().map(|spec| parse_ids(spec, &ids_in_use(todo_list))).transpose()?;
+    let filter = select.filter.as_deref().map(Query::parse).transpose()?;
+    if ids.is_none() && filter.is_none() {
+        return Err(Error::Invalid(format!("Specify which tasks to {}: ids like 1,4,7-10 and/or --where", action.verb())));
     }
+    let selected = todo_list.select(ids.as_deref(), filter.as_ref())?;
+    let children = ChildPolicy::from_name(&select.children)?;
 
-    fn add(&mut self, title: String, priority: Option<String>, due: Option<String>, categories: Vec<String>) -> io::Result<()> {
-        let mut todo = Todo::new(title.clone(), priority, due, categories);
-        todo.id = self.todos.len() + 1;
-        self.todos.push(todo);
-        self.save()?;
-        println!("{} Added new todo: {}", "✓".green(), title.cyan());
-        Ok(())
+    if selected.is_empty() {
+        println!("{}", colors().warn.paint("No matching tasks found!"));
+        return Ok(());
     }
 
-    fn list(&self, show_completed: bool, priority_filter: Option<String>, category_filter: Option<String>) {
-        println!("\n{}", "📋 Tasks".blue());
+    if select.dry_run {
+        println!("\n{} would {} {} tasks", colors().label.paint("Dry run:"), action.verb(), selected.len());
         println!("{}", "=".repeat(50));
+        let todos: Vec<&Todo> = selected.iter().filter_map(|&id| todo_list.get(id)).collect();
+        display_todos(&todo_list.overview(), &todos);
+        return Ok(());
+    }
 
-        let mut found = false;
-        for todo in &self.todos {
-            if show_completed == todo.completed {
-                // Apply filters
-                if let Some(ref priority) = priority_filter {
-                    let todo_priority = match todo.priority {
-                        Priority::High => "high",
-                        Priority::Medium => "medium",
-                        Priority::Low => "low",
-                    };
-                    if priority != todo_priority {
-                        continue;
-                    }
-                }
+    if selected.len() > CONFIRM_THRESHOLD
+        && !select.yes
+        && !confirm(&format!("About to {} {} tasks. Continue?", action.verb(), selected.len()))
+    {
+        println!("{} Nothing changed", style::warn());
+        return Ok(());
+    }
 
-                if let Some(ref category) = category_filter {
-                    if !todo.categories.contains(&category.to_string()) {
-                        continue;
-                    }
-                }
+    // A single explicit id keeps the precise "already completed" style messages.
+    if let (Action::SetStatus(status), [id]) = (&action, &selected[..]) {
+        if todo_list.get(*id).is_some_and(|todo| todo.status == *status) {
+            return report(Err(Error::AlreadyInStatus(*id, *status)));
+        }
+    }
 
-                found = true;
-                self.display_todo(todo);
+    if let Action::SetStatus(Status::Done) = action {
+        for &id in &selected {
+            let open: Vec<String> = todo_list
+                .open_prerequisites(id)
+                .into_iter()
+                .filter(|prerequisite| !selected.contains(prerequisite))
+                .map(|prerequisite| format!("#{}", prerequisite))
+                .collect();
+            if !open.is_empty() {
+                println!("{} Task {} still depends on open {}", style::warn(), id, open.join(", "));
             }
         }
+    }
 
-        if !found {
-            println!("{}", "No matching tasks found!".yellow());
+    let changed: Vec<String> = match action {
+        Action::SetStatus(status) => {
+            let changed = todo_list.set_status_many(&selected, status, children)?;
+            let skipped = selected.iter().filter(|id| !changed.contains(id)).count();
+            if skipped > 0 {
+                println!("{} Skipped {} tasks already {}", style::warn(), skipped, status.as_str());
+            }
+            changed.iter().filter_map(|&id| todo_list.get(id)).map(|todo| todo.title.clone()).collect()
         }
-        println!();
+        Action::Delete => todo_list.delete_many(&selected, children)?.into_iter().map(|todo| todo.title).collect(),
+    };
+    for title in changed {
+        println!("{} {}", action.done_message(), colors().accent.paint(&title));
+    }
+    report_spawned(todo_list);
+    Ok(())
+}
+
+/// Announces the next occurrences created by completing recurring tasks.
+fn report_spawned(todo_list: &TodoList) {
+    for todo in todo_list.spawned().iter().filter_map(|&id| todo_list.get(id)) {
+        let due = todo.due_date.map(format_due_date).unwrap_or_default();
+        println!(
+            "{} Next: [{}] {} {}",
+            colors().repeat.paint(&symbols().repeat),
+            colors().accent.paint(&todo.id.to_string()),
+            todo.title,
+            colors().muted.paint(&format!("(due {})", due))
+        );
     }
+}
 
-    fn search(&self, query: &str) {
-        println!("\n{} '{}'", "🔍 Search results for".blue(), query.cyan());
-        println!("{}", "=".repeat(50));
+fn format_priority(priority: Priority) -> ColoredString {
+    match priority {
+        Priority::High => colors().high.paint(&format!("{} HIGH", symbols().high)),
+        Priority::Medium => colors().medium.paint(&format!("{} MED", symbols().medium)),
+        Priority::Low => colors().low.paint(&format!("{} LOW", symbols().low)),
+    }
+}
 
-        let mut found = false;
-        for todo in &self.todos {
-            if todo.title.to_lowercase().contains(&query.to_lowercase()) {
-                found = true;
-                self.display_todo(todo);
-            }
-        }
+/// Prints one task, indented `depth` levels when it is shown under its parent.
+fn display_todo(overview: &Overview, todo: &Todo, depth: usize) {
+    display_todo_titled(overview, todo, depth, &colors().title.paint(&todo.title).to_string());
+}
 
-        if !found {
-            println!("{}", "No matching tasks found!".yellow());
-        }
-        println!();
+/// [`display_todo`] with the title already styled, e.g. with search matches highlighted.
+fn display_todo_titled(overview: &Overview, todo: &Todo, depth: usize, title: &str) {
+    let (colors, symbols) = (colors(), symbols());
+    let shows = |field: &str| theme().shows(field);
+    let indent = "    ".repeat(depth);
+    let (style, status, label) = match todo.status {
+        Status::Todo => (&colors.todo, &symbols.todo, ""),
+        Status::InProgress => (&colors.in_progress, &symbols.in_progress, " IN PROGRESS"),
+        Status::Blocked => (&colors.blocked, &symbols.blocked, " BLOCKED"),
+        Status::Done => (&colors.done, &symbols.done, ""),
+        Status::Cancelled => (&colors.cancelled, &symbols.cancelled, " CANCELLED"),
+    };
+    let label = if shows("status") && !label.is_empty() { style.paint(label) } else { "".normal() };
+
+    let repeat = match &todo.recurrence {
+        Some(recurrence) if shows("repeat") => colors.repeat.paint(&format!(" {} {}", symbols.repeat, recurrence.describe())),
+        _ => "".normal(),
+    };
+    let notes = if todo.notes.is_empty() || !shows("notes") { "".normal() } else { colors.notes.paint(&format!(" {}", symbols.notes)) };
+    let priority = if shows("priority") { format!(" {}", format_priority(todo.priority)) } else { String::new() };
+    let created = if shows("created") {
+        format!(" {}", colors.muted.paint(&format!("(created: {})", todo.created_at.format("%Y-%m-%d %H:%M"))))
+    } else {
+        String::new()
+    };
+
+    println!(
+        "{}{} [{}] {}{}{}{}{}{}",
+        indent,
+        style.paint(status),
+        colors.accent.paint(&todo.id.to_string()),
+        title,
+        notes,
+        priority,
+        label,
+        repeat,
+        created
+    );
+
+    if !todo.categories.is_empty() && shows("tags") {
+        println!(
+            "{}     {} {}",
+            indent,
+            colors.label.paint(&format!("{} categories:", symbols.detail)),
+            colors.muted.paint(&todo.categories.join(", "))
+        );
     }
 
-    fn display_todo(&self, todo: &Todo) {
-        let status = if todo.completed {
-            "✓".green()
-        } else {
-            "○".yellow()
+    if let Some(due_date) = todo.due_date.filter(|_| shows("due")) {
+        let date = format_due_date(due_date);
+        let today = Local::now().date_naive();
+        let due = match todo.days_until_due(today) {
+            Some(days) if todo.status.is_open() => {
+                let text = format!("{} ({})", date, relative_due(days));
+                if days < 0 {
+                    colors.overdue.paint(&text)
+                } else if days == 0 {
+                    colors.due_today.paint(&text)
+                } else {
+                    colors.muted.paint(&text)
+                }
+            }
+            _ => colors.muted.paint(&date),
+        };
+        let zone = match &todo.due_zone {
+            Some(zone) if local_zone().as_ref() != Some(zone) => format!(" (set in {})", zone),
+            _ => String::new(),
         };
-        
+        let style = if todo.is_overdue(today) { &colors.overdue } else { &colors.due };
+        println!("{}     {} {}{}", indent, style.paint(&format!("{} due:", symbols.detail)), due, colors.muted.paint(&zone));
+    }
+
+    if let Some(completed_at) = todo.completed_at.filter(|_| shows("completed")) {
         println!(
-            "{} [{}] {} {} {}",
-            status,
-            todo.id.to_string().cyan(),
-            todo.title.white(),
-            todo.format_priority(),
-            format!("(created: {})", 
-                todo.created_at.format("%Y-%m-%d %H:%M")).dimmed()
+            "{}     {} {}",
+            indent,
+            colors.done.paint(&format!("{} completed:", symbols.detail)),
+            colors.muted.paint(&completed_at.format("%Y-%m-%d %H:%M").to_string())
         );
+    }
+
+    if !todo.depends_on.is_empty() && shows("depends_on") {
+        let open = overview.open_prerequisites(todo);
+        let prerequisites: Vec<String> = todo
+            .depends_on
+            .iter()
+            .map(|prerequisite| {
+                let text = format!("#{}", prerequisite);
+                if open.contains(prerequisite) { colors.warn.paint(&text).to_string() } else { colors.muted.paint(&text).to_string() }
+            })
+            .collect();
+        let label = if open.is_empty() || !todo.status.is_open() {
+            colors.label.paint(&format!("{} depends on:", symbols.detail))
+        } else {
+            colors.warn.paint(&format!("{} waiting on:", symbols.detail))
+        };
+        println!("{}     {} {}", indent, label, prerequisites.join(", "));
+    }
+
+    if let Some(progress) = todo.checklist_progress().filter(|_| shows("checklist")) {
+        println!("{}     {} {}", indent, colors.label.paint(&format!("{} checklist:", symbols.detail)), progress_text(progress));
+    }
+
+    if let Some(progress) = overview.progress(todo.id).filter(|_| shows("subtasks")) {
+        println!("{}     {} {}", indent, colors.label.paint(&format!("{} subtasks:", symbols.detail)), progress_text(progress));
+    }
+}
+
+/// "x/y done", highlighted once everything is done.
+fn progress_text(progress: Progress) -> ColoredString {
+    let text = format!("{}/{} done", progress.done, progress.total);
+    if progress.done == progress.total { colors().done.paint(&text) } else { colors().muted.paint(&text) }
+}
+
+fn display_groups(overview: &Overview, todos: &[&Todo], group_by: GroupBy) {
+    let groups = group_todos(todos, group_by, Local::now().date_naive());
+    for group in &groups {
+        println!("{} {}", colors().heading.paint(&symbols().group), colors().heading.paint(&format!("{} ({})", group.title, group.todos.len())));
+        for node in tree(&group.todos) {
+            display_todo(overview, node.todo, node.depth);
+        }
+        println!();
+    }
+
+    if groups.is_empty() {
+        println!("{}", colors().warn.paint("No matching tasks found!"));
+        println!();
+    }
+}
+
+/// Prints a search result with the matched parts of its title, tags and notes highlighted.
+fn display_hit(overview: &Overview, hit: &Hit) {
+    let todo = hit.todo;
+    display_todo_titled(overview, todo, 0, &highlight(&todo.title, &hit.spans_in(Field::Title)));
+
+    let tags: Vec<String> = todo
+        .categories
+        .iter()
+        .enumerate()
+        .map(|(index, tag)| (tag, hit.spans_in(Field::Tag(index))))
+        .filter(|(_, spans)| !spans.is_empty())
+        .map(|(tag, spans)| highlight(tag, &spans))
+        .collect();
+    if !tags.is_empty() {
+        println!("     {} {}", colors().label.paint(&format!("{} matched tags:", symbols().detail)), tags.join(", "));
+    }
+
+    // The first two lines of the notes that matched, with the spans moved to line offsets.
+    let mut lines: Vec<(usize, usize)> = Vec::new();
+    for (start, _) in hit.spans_in(Field::Notes) {
+        let line_start = todo.notes[..start].rfind('\n').map_or(0, |newline| newline + 1);
+        let line_end = todo.notes[start..].find('\n').map_or(todo.notes.len(), |newline| start + newline);
+        if !lines.contains(&(line_start, line_end)) {
+            lines.push((line_start, line_end));
+        }
+    }
+    for &(line_start, line_end) in lines.iter().take(2) {
+        let spans: Vec<(usize, usize)> = hit
+            .spans_in(Field::Notes)
+            .into_iter()
+            .filter(|&(start, _)| start >= line_start && start < line_end)
+            .map(|(start, end)| (start - line_start, end.min(line_end) - line_start))
+            .collect();
+        println!("     {} {}", colors().label.paint(&format!("{} notes:", symbols().detail)), highlight(todo.notes[line_start..line_end].trim_end(), &spans));
+    }
+}
 
-        if !todo.categories.is_empty() {
-            println!(
-                "     {} {}", 
-                "↳ categories:".blue(),
-                todo.categories.join(", ").dimmed()
-            );
+/// Marks the sorted byte `ranges` of `text`; overlapping ranges are merged.
+fn highlight(text: &str, ranges: &[(usize, usize)]) -> String {
+    let mut out = String::new();
+    let mut last = 0;
+    for &(start, end) in ranges {
+        let (start, end) = (start.max(last), end.min(text.len()));
+        if start >= end {
+            continue;
         }
+        out.push_str(&text[last..start]);
+        out.push_str(&colors().highlight.paint(&text[start..end]).to_string());
+        last = end;
+    }
+    out.push_str(&text[last..]);
+    out
+}
 
-        if let Some(due_date) = todo.due_date {
-            println!(
-                "     {} {}", 
-                "↳ due:".yellow(),
-                due_date.format("%Y-%m-%d").to_string().dimmed()
-            );
+/// Prints `todos` with subtasks indented under their parents.
+fn display_todos(overview: &Overview, todos: &[&Todo]) {
+    for node in tree(todos) {
+        display_todo(overview, node.todo, node.depth);
+    }
+
+    if todos.is_empty() {
+        println!("{}", colors().warn.paint("No matching tasks found!"));
+    }
+    println!();
+}
+
+/// Prints the user-facing errors that the CLI reports without failing the process.
+fn report(result: Result<()>) -> Result<()> {
+    match result {
+        Err(Error::NotFound(id)) => {
+            println!("{} Todo with id {} not found", style::error(), id);
+            Ok(())
+        }
+        Err(Error::AlreadyInStatus(id, Status::Done)) => {
+            println!("{} Task {} is already completed!", style::warn(), id);
+            Ok(())
+        }
+        Err(Error::AlreadyInStatus(id, status)) => {
+            println!("{} Task {} is already {}!", style::warn(), id, status.as_str());
+            Ok(())
         }
+        Err(err @ (Error::NothingToUndo | Error::NothingToRedo)) => {
+            println!("{} {}", style::warn(), err);
+            Ok(())
+        }
+        Err(Error::BackupNotFound(index)) => {
+            println!("{} Backup {} not found", style::error(), index + 1);
+            Ok(())
+        }
+        other => other,
+    }
+}
+
+fn parse_priority(name: &str) -> Result<Priority> {
+    Priority::from_name(name)
+        .ok_or_else(|| Error::Invalid(format!("Unknown priority '{}' (expected high, medium or low)", name)))
+}
 
-        if let Some(completed_at) = todo.completed_at {
-            println!(
-                "     {} {}", 
-                "↳ completed:".green(),
-                completed_at.format("%Y-%m-%d %H:%M").to_string().dimmed()
-            );
+fn parse_status(name: &str) -> Result<Status> {
+    Status::from_name(name).ok_or_else(|| {
+        Error::Invalid(format!("Unknown status '{}' (expected todo, in-progress, blocked, done or cancelled)", name))
+    })
+}
+
+fn print_banner() {
+    println!("\n{}", colors().banner.paint(&symbols().banner));
+}
+
+fn doctor(path: &Path, dry_run: bool, backup_limit: usize) -> Result<()> {
+    match TodoList::open_with_backup_limit(path, backup_limit) {
+        Ok(todo_list) => {
+            println!("{} {} is healthy ({} tasks)", style::ok(), path.display(), todo_list.todos().len());
+            return Ok(());
         }
+        Err(err @ Error::Corrupt { .. }) => println!("{} {}", style::warn(), err),
+        Err(err) => return Err(err),
+    }
+
+    let (mut todo_list, recovery) = TodoList::recover(path)?;
+    todo_list.set_backup_limit(backup_limit);
+    for warning in &recovery.warnings {
+        println!("     {} {}", colors().warn.paint(&symbols().detail), colors().muted.paint(warning));
+    }
+    for repair in todo_list.repairs() {
+        println!("     {} id {} reassigned to {}", colors().warn.paint(&symbols().detail), repair.old_id, repair.new_id);
+    }
+    println!(
+        "{} Recovered {} tasks, dropped {}",
+        style::ok(),
+        colors().accent.paint(&recovery.recovered.to_string()),
+        colors().accent.paint(&recovery.skipped.to_string())
+    );
+
+    if dry_run {
+        println!("{}", colors().muted.paint("Dry run: nothing was written."));
+    } else {
+        todo_list.save_recovered()?;
+        println!("{} Saved recovered list and cleared the undo history; the damaged file was kept as a backup", style::ok());
     }
+    Ok(())
+}
+
+fn list_backups(path: &Path) -> Result<()> {
+    println!("\n{}", colors().label.paint(&format!("{} Backups", symbols().backups)));
+    println!("{}", "=".repeat(50));
+    let backups = TodoList::backups_of(path)?;
+    for (index, backup) in backups.iter().enumerate() {
+        println!(
+            "[{}] {} {}",
+            colors().accent.paint(&(index + 1).to_string()),
+            backup.created_at.format("%Y-%m-%d %H:%M:%S"),
+            colors().muted.paint(&backup.path.display().to_string())
+        );
+    }
+    if backups.is_empty() {
+        println!("{}", colors().warn.paint("No backups yet!"));
+    }
+    println!();
+    Ok(())
+}
+
+/// Prints `records` to stdout in a machine-readable `format`. A reader that
+/// stops early, like `head`, is not an error where SIGPIPE does not end the
+/// process (see [`reset_sigpipe`]).
+fn print_records(format: &Format, records: &[Record]) -> Result<()> {
+    match format.write(&mut io::stdout().lock(), records) {
+        Err(Error::Io(err)) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
+fn run(cli: Cli, format: Format, backup_limit: usize) -> Result<()> {
+    let Location { path, source } = location::resolve(cli.file.as_deref());
+
+    let reads = matches!(cli.command, Command::List { .. } | Command::Search { .. } | Command::Due { .. } | Command::Show { .. });
+    if !format.is_text() && !reads {
+        return Err(Error::Invalid("--format only applies to the commands that print tasks: list, search, due and show".to_string()));
+    }
+
+    // These commands must work even when the data file cannot be loaded.
+    match cli.command {
+        Command::Path => {
+            println!("{} {}", colors().accent.paint(&path.display().to_string()), colors().muted.paint(&format!("({})", source)));
+            return Ok(());
+        },
+        Command::Doctor { dry_run } => return doctor(&path, dry_run, backup_limit),
+        Command::Restore { number: None } => return list_backups(&path),
+        Command::Restore { number: Some(0) } => {
+            println!("{} Backup numbers start at 1", style::error());
+            return Ok(());
+        },
+        Command::Restore { number: Some(number) } => {
+            return report(TodoList::restore(&path, number - 1, backup_limit).map(|(_, backup)| {
+                println!(
+                    "{} Restored backup from {}",
+                    style::ok(),
+                    colors().accent.paint(&backup.created_at.format("%Y-%m-%d %H:%M:%S").to_string())
+                );
+            }));
+        },
+        _ => {}
+    }
+
+    // Notices go to stderr when stdout is meant for other programs.
+    let notice = |text: String| if format.is_text() { println!("{}", text) } else { eprintln!("{}", text) };
+    let mut todo_list = TodoList::open_with_backup_limit(&path, backup_limit)?;
+    if let Some(version) = todo_list.migrated_from() {
+        notice(format!("{} Upgraded {} from schema v{} to v{}", style::warn(), path.display(), version, schema::CURRENT_VERSION));
+    }
+    for repair in todo_list.repairs() {
+        notice(format!("{} Reassigned duplicate id {} to {}", style::warn(), repair.old_id, repair.new_id));
+    }
+    if source == Source::Legacy {
+        notice(format!(
+            "{} Using ./{} from an older version; move it to {} to use it from any directory",
+            style::warn(),
+            location::DEFAULT_FILE,
+            location::data_dir_file().display()
+        ));
+    }
+
+    match cli.command {
+        Command::Add { title, priority, due, tags, parent, repeat } => {
+            let mut todo = Todo::new(title, priority, due, tags)?;
+            todo.parent_id = parent;
+            todo.recurrence = repeat.as_deref().map(Recurrence::parse).transpose()?;
+            let todo = todo_list.add_todo(todo)?;
+            println!("{} Added new todo: {}", style::ok(), colors().accent.paint(&todo.title));
+        },
+        Command::Edit { id, interactive: true, .. } => {
+            let edit = match todo_list.get(id) {
+                Some(todo) => interactive::edit(todo)?,
+                None => return report(Err(Error::NotFound(id))),
+            };
+            if edit.is_empty() {
+                println!("{} No changes made", style::warn());
+            } else {
+                let todo = todo_list.edit(id, edit)?;
+                println!("{} Updated: {}", style::ok(), colors().accent.paint(&todo.title));
+            }
+        },
+        Command::Edit { id, title, priority, due, clear_due, add_tags, remove_tags, repeat, no_repeat, parent, no_parent, .. } => {
+            let edit = TodoEdit {
+                title,
+                priority: priority.as_deref().map(parse_priority).transpose()?,
+                due_date: match due {
+                    Some(due) => Some(Some(parse_due_date(&due)?)),
+                    None if clear_due => Some(None),
+                    None => None,
+                },
+                add_categories: add_tags,
+                remove_categories: remove_tags,
+                recurrence: match repeat {
+                    Some(repeat) => Some(Some(Recurrence::parse(&repeat)?)),
+                    None if no_repeat => Some(None),
+                    None => None,
+                },
+                notes: None,
+                parent_id: match parent {
+                    Some(parent) => Some(Some(parent)),
+                    None if no_parent => Some(None),
+                    None => None,
+                },
+            };
+            if edit.is_empty() {
+                println!("{} Nothing to change; pass --title, --priority, --due, --clear-due, --add-tag, --remove-tag, --repeat, --no-repeat, --parent, --no-parent or --interactive", style::warn());
+            } else {
+                report(todo_list.edit(id, edit).map(|todo| {
+                    println!("{} Updated: {}", style::ok(), colors().accent.paint(&todo.title));
+                }))?
+            }
+        },
+        Command::List { filter, sort, group_by } => {
+            let ready = filter.ready;
+            let filter = filter.to_query()?;
+            let sort = sort.as_deref().map(SortKey::parse_list).transpose()?.unwrap_or_default();
+            let group_by = group_by.as_deref().map(GroupBy::from_name).transpose()?;
 
-    fn complete(&mut self, id: usize) -> io::Result<()> {
-        let title = match self.todos.iter_mut().find(|t| t.id == id) {
-            Some(todo) => {
-                if todo.completed {
-                    println!("{} Task {} is already completed!", "!".yellow(), id);
-                    return Ok(());
+            let overview = todo_list.overview();
+            let mut todos = todo_list.filter(&filter);
+            if ready {
+                todos.retain(|todo| overview.is_ready(todo));
+            }
+            sort_todos(&mut todos, &sort);
+            if !format.is_text() {
+                if group_by.is_some() {
+                    return Err(Error::Invalid("--group-by only applies to text output".to_string()));
                 }
-                todo.completed = true;
-                todo.completed_at = Some(Local::now());
-                todo.title.clone()
+                let records: Vec<Record> = todos.into_iter().map(Record::from).collect();
+                return print_records(&format, &records);
+            }
+            println!("\n{}", colors().label.paint(&format!("{} Tasks", symbols().tasks)));
+            println!("{}", "=".repeat(50));
+            match group_by {
+                Some(group_by) => display_groups(&overview, &todos, group_by),
+                None => display_todos(&overview, &todos),
             }
-            None => {
-                println!("{} Todo with id {} not found", "✗".red(), id);
+        },
+        Command::Search { query, regex, limit, filter } => {
+            let filter = filter.as_deref().map(Query::parse).transpose()?.unwrap_or(Query::All);
+            let search = if regex { Search::regex(&query)? } else { Search::fuzzy(&query) };
+            let hits: Vec<Hit> = todo_list
+                .find(&search)
+                .into_iter()
+                .filter(|hit| filter.matches(hit.todo))
+                .take(limit.unwrap_or(usize::MAX))
+                .collect();
+            if !format.is_text() {
+                let records: Vec<Record> = hits.iter().map(|hit| Record::scored(hit.todo, hit.score)).collect();
+                return print_records(&format, &records);
+            }
+            println!("\n{} '{}'", colors().label.paint(&format!("{} Search results for", symbols().search)), colors().accent.paint(&query));
+            println!("{}", "=".repeat(50));
+            let overview = todo_list.overview();
+            for hit in &hits {
+                display_hit(&overview, hit);
+            }
+            if hits.is_empty() {
+                println!("{}", colors().warn.paint("No matching tasks found!"));
+            }
+            println!();
+        },
+        Command::Show { id } => match todo_list.get(id) {
+            Some(todo) if !format.is_text() => print_records(&format, &[Record::from(todo)])?,
+            Some(todo) => show::show(&todo_list, todo),
+            None => report(Err(Error::NotFound(id)))?,
+        },
+        Command::Note { id, text } => {
+            let text = match text {
+                Some(text) => text,
+                None => io::read_to_string(io::stdin())?,
+            };
+            report(todo_list.note(id, &text).map(|todo| {
+                println!("{} Added note to {}", style::ok(), colors().accent.paint(&todo.title));
+            }))?
+        },
+        Command::Check { id, add, done, undone, remove } => {
+            if add.is_empty() && done.is_none() && undone.is_none() && remove.is_none() {
+                println!("{} Nothing to change; pass --add, --done, --undone or --remove", style::warn());
                 return Ok(());
             }
-        };
-        
-        self.save()?;
-        println!("{} Completed: {}", "✓".green(), title.cyan());
-        Ok(())
-    }
-
-    fn delete(&mut self, id: usize) -> io::Result<()> {
-        let title = match self.todos.iter().position(|t| t.id == id) {
-            Some(index) => {
-                let todo = self.todos.remove(index);
-                todo.title
+            let index = |number: usize| number.checked_sub(1).ok_or_else(|| Error::Invalid("Item numbers start at 1".to_string()));
+            let mut result = Ok(());
+            for text in &add {
+                result = result.and_then(|_| todo_list.add_checklist_item(id, text).map(|_| ()));
+            }
+            if let Some(number) = done {
+                result = result.and_then(|_| todo_list.set_checklist_item(id, index(number)?, true).map(|_| ()));
+            }
+            if let Some(number) = undone {
+                result = result.and_then(|_| todo_list.set_checklist_item(id, index(number)?, false).map(|_| ()));
+            }
+            if let Some(number) = remove {
+                result = result.and_then(|_| todo_list.remove_checklist_item(id, index(number)?).map(|_| ()));
             }
-            None => {
-                println!("{} Todo with id {} not found", "✗".red(), id);
+            match result {
+                Ok(()) => show::show(&todo_list, todo_list.get(id).unwrap()),
+                Err(err) => report(Err(err))?,
+            }
+        },
+        Command::Depend { id, on, remove } => {
+            let prerequisites = parse_ids(&on, &ids_in_use(&todo_list))?;
+            let result = if remove { todo_list.undepend(id, &prerequisites) } else { todo_list.depend(id, &prerequisites) };
+            report(result.map(|todo| {
+                let on = todo.depends_on.iter().map(|id| format!("#{}", id)).collect::<Vec<_>>().join(", ");
+                match on.as_str() {
+                    "" => println!("{} {} has no prerequisites", style::ok(), colors().accent.paint(&todo.title)),
+                    on => println!("{} {} waits on {}", style::ok(), colors().accent.paint(&todo.title), on),
+                }
+            }))?
+        },
+        Command::Complete { select } => run_bulk(&mut todo_list, select, Action::SetStatus(Status::Done))?,
+        Command::Start { select } => run_bulk(&mut todo_list, select, Action::SetStatus(Status::InProgress))?,
+        Command::Block { select } => run_bulk(&mut todo_list, select, Action::SetStatus(Status::Blocked))?,
+        Command::Reopen { select } => run_bulk(&mut todo_list, select, Action::SetStatus(Status::Todo))?,
+        Command::Cancel { select } => run_bulk(&mut todo_list, select, Action::SetStatus(Status::Cancelled))?,
+        Command::Delete { select } => run_bulk(&mut todo_list, select, Action::Delete)?,
+        Command::Due { days, exit_code } => {
+            let report = DueReport::new(todo_list.todos(), Local::now().date_naive(), days);
+            // Reads only, so there is nothing left to save before exiting.
+            let exit = report.exit_code().filter(|_| exit_code);
+
+            if !format.is_text() {
+                let records: Vec<Record> = report.todos().into_iter().map(Record::from).collect();
+                print_records(&format, &records)?;
+                if let Some(code) = exit {
+                    process::exit(code);
+                }
                 return Ok(());
             }
-        };
-        
-        self.save()?;
-        println!("{} Deleted: {}", "✗".red(), title.cyan());
-        Ok(())
+            println!("\n{}", colors().label.paint(&format!("{} Due", symbols().due)));
+            println!("{}", "=".repeat(50));
+            let sections = [("Overdue", &report.overdue), ("Today", &report.today), ("Upcoming", &report.upcoming)];
+            let overview = todo_list.overview();
+            for (title, todos) in sections {
+                if todos.is_empty() {
+                    continue;
+                }
+                let header = format!("{} ({})", title, todos.len());
+                let header = if title == "Overdue" { colors().overdue.paint(&header) } else { colors().heading.paint(&header) };
+                println!("{} {}", colors().heading.paint(&symbols().group), header);
+                for todo in todos {
+                    display_todo(&overview, todo, 0);
+                }
+                println!();
+            }
+            if report.is_empty() {
+                println!("{}", colors().ok.paint(&format!("Nothing due in the next {} days!", days)));
+                println!();
+            }
+
+            if let Some(code) = exit {
+                process::exit(code);
+            }
+        },
+        Command::Undo => report(todo_list.undo().map(|operation| {
+            println!("{} Undid: {}", colors().ok.paint(&symbols().undo), colors().accent.paint(&operation.description));
+        }))?,
+        Command::Redo => report(todo_list.redo().map(|operation| {
+            println!("{} Redid: {}", colors().ok.paint(&symbols().redo), colors().accent.paint(&operation.description));
+        }))?,
+        Command::History => {
+            println!("\n{}", colors().label.paint(&format!("{} History", symbols().history)));
+            println!("{}", "=".repeat(50));
+            let history = todo_list.history();
+            for (index, operation) in history.operations.iter().enumerate().rev() {
+                let line = format!(
+                    "{} {}",
+                    operation.at.format("%Y-%m-%d %H:%M:%S"),
+                    operation.description
+                );
+                if index < history.position {
+                    println!("{} {}", style::ok(), line);
+                } else {
+                    println!("{} {} {}", colors().muted.paint(&symbols().undo), colors().muted.paint(&line), colors().muted.paint("(undone)"));
+                }
+            }
+            if history.operations.is_empty() {
+                println!("{}", colors().warn.paint("No changes recorded yet!"));
+            }
+            println!();
+        },
+        Command::Path | Command::Doctor { .. } | Command::Restore { .. } => unreachable!(),
     }
+
+    Ok(())
 }
 
-fn print_banner() {
-    println!("\n{}", r#"
-╭────────────────────────────────╮
-│     RUST TODO MANAGER          │
-╰────────────────────────────────╯"#.cyan());
+/// Rust ignores SIGPIPE, turning every print to a closed pipe (`todo list | head`)
+/// into a panic. Like other command-line tools, stop quietly instead.
+#[cfg(unix)]
+fn reset_sigpipe() {
+    // SAFETY: runs before any other thread exists and only restores the default handler.
+    unsafe {
+        libc::signal(libc::SIGPIPE, libc::SIG_DFL);
+    }
 }
 
-fn main() -> io::Result<()> {
-    print_banner();
-    let mut todo_list = TodoList::new()?;
-    let cli = Cli::from_args();
+#[cfg(not(unix))]
+fn reset_sigpipe() {}
 
-    match cli {
-        Cli::Add { title, priority, due, tags } => {
-            todo_list.add(title, priority, due, tags)?
-        },
-        Cli::List { completed, priority, tag } => {
-            todo_list.list(completed, priority, tag)
-        },
-        Cli::Search { query } => todo_list.search(&query),
-        Cli::Complete { id } => todo_list.complete(id)?,
-        Cli::Delete { id } => todo_list.delete(id)?,
+fn main() {
+    reset_sigpipe();
+    let cli = Cli::from_args();
+    colored::control::set_override(cli.color.enabled());
+    let Config { mut theme, backup_limit } = Config::load(&config::config_path()).unwrap_or_else(|err| {
+        eprintln!("{} {}", style::error(), err);
+        process::exit(1);
+    });
+    if let Some(name) = cli.theme.as_deref() {
+        theme = Theme::bundled(name).unwrap();
+    }
+    if cli.ascii {
+        theme.symbols = Symbols::ascii();
+    }
+    style::init(theme);
+    let format = cli.format.as_deref().map(Format::parse).transpose().unwrap_or_else(|err| {
+        eprintln!("{} {}", style::error(), err);
+        process::exit(1);
+    });
+    // Machine-readable output, pipes and logs get no banner.
+    if !cli.no_banner && format.as_ref().is_none_or(Format::is_text) && io::stdout().is_terminal() {
+        print_banner();
     }
 
-    Ok(())
-}
\ No newline at end of file
+    if let Err(err) = run(cli, format.unwrap_or_default(), backup_limit) {
+        eprintln!("{} {}", style::error(), err);
+        process::exit(1);
+    }
+}
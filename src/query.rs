@@ -0,0 +1,420 @@
+//! A small filter language for selecting todos, e.g.
+//!
+//! ```text
+//! priority:high and (tag:work or tag:urgent) and due<2024-12-31 and not done
+//! ```
+//!
+//! Terms are `field:value` or `field<op>value` with `<`, `<=`, `>`, `>=` or `=`:
+//!
+//! | field       | values                                               |
+//! |-------------|------------------------------------------------------|
+//! | `id`        | a number; comparisons allowed                        |
+//! | `title`     | text contained in the title, case-insensitive        |
+//! | `tag`       | a category                                           |
+//! | `status`    | `todo`, `in-progress`, `blocked`, `done`, `cancelled` or `open` |
+//! | `priority`  | `high`, `medium` or `low`; `>` means more urgent     |
+//! | `due`, `created`, `completed` | `YYYY-MM-DD` or `today`; `:none` / `:any` test presence |
+//!
+//! A bare status name (`done`, `open`, ...) is short for `status:<name>` and
+//! `overdue` matches open todos due before today; any other bare word or
+//! `"quoted text"` matches titles. Terms combine with `and`,
+//! `or`, `not` and parentheses; adjacent terms without an operator are and-ed,
+//! so `tag:work priority:low` works too.
+
+use std::cmp::Ordering;
+
+use chrono::{Local, NaiveDate};
+
+use crate::dates::local_date;
+use crate::error::{Error, Result};
+use crate::todo::{Priority, Status, Todo};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cmp {
+    Eq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Cmp {
+    fn test(self, ordering: Ordering) -> bool {
+        match self {
+            Cmp::Eq => ordering == Ordering::Equal,
+            Cmp::Lt => ordering == Ordering::Less,
+            Cmp::Le => ordering != Ordering::Greater,
+            Cmp::Gt => ordering == Ordering::Greater,
+            Cmp::Ge => ordering != Ordering::Less,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DateField {
+    Due,
+    Created,
+    Completed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term {
+    Id(Cmp, usize),
+    /// Case-insensitive substring of the title; stored lowercased.
+    Title(String),
+    Tag(String),
+    /// Any of these statuses.
+    Status(Vec<Status>),
+    Priority(Cmp, Priority),
+    Date(DateField, Cmp, NaiveDate),
+    /// Whether the date is set at all.
+    HasDate(DateField, bool),
+    /// Open and due before today.
+    Overdue,
+}
+
+/// A parsed filter expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    /// Matches every todo; the result of parsing an empty expression.
+    All,
+    Term(Term),
+    Not(Box<Query>),
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Self> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { input, tokens, pos: 0 };
+        if parser.tokens.is_empty() {
+            return Ok(Query::All);
+        }
+        let query = parser.parse_or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(query),
+            Some(token) => Err(parser.error(token.start, format!("unexpected '{}'", token.text))),
+        }
+    }
+
+    /// Combines two queries, treating [`Query::All`] as the identity.
+    pub fn and(self, other: Query) -> Query {
+        match (self, other) {
+            (Query::All, query) | (query, Query::All) => query,
+            (left, right) => Query::And(Box::new(left), Box::new(right)),
+        }
+    }
+
+    /// Whether any term constrains the status, so callers can skip their
+    /// default of showing only open todos.
+    pub fn mentions_status(&self) -> bool {
+        match self {
+            Query::All => false,
+            Query::Term(term) => matches!(
+                term,
+                Term::Status(_) | Term::Overdue | Term::HasDate(DateField::Completed, _) | Term::Date(DateField::Completed, _, _)
+            ),
+            Query::Not(query) => query.mentions_status(),
+            Query::And(left, right) | Query::Or(left, right) => left.mentions_status() || right.mentions_status(),
+        }
+    }
+
+    pub fn matches(&self, todo: &Todo) -> bool {
+        match self {
+            Query::All => true,
+            Query::Term(term) => term.matches(todo),
+            Query::Not(query) => !query.matches(todo),
+            Query::And(left, right) => left.matches(todo) && right.matches(todo),
+            Query::Or(left, right) => left.matches(todo) || right.matches(todo),
+        }
+    }
+}
+
+impl Term {
+    pub fn matches(&self, todo: &Todo) -> bool {
+        match self {
+            Term::Id(cmp, id) => cmp.test(todo.id.cmp(id)),
+            Term::Title(text) => todo.title.to_lowercase().contains(text),
+            Term::Tag(tag) => todo.categories.contains(tag),
+            Term::Status(statuses) => statuses.contains(&todo.status),
+            Term::Priority(cmp, priority) => cmp.test(rank(todo.priority).cmp(&rank(*priority))),
+            Term::Date(field, cmp, date) => date_of(todo, *field).is_some_and(|value| cmp.test(value.cmp(date))),
+            Term::HasDate(field, present) => date_of(todo, *field).is_some() == *present,
+            Term::Overdue => todo.is_overdue(Local::now().date_naive()),
+        }
+    }
+}
+
+/// The `list` flags that choose which todos are shown, combined into one
+/// [`Query`] by [`ListFilter::to_query`].
+#[derive(Debug, Clone, Default)]
+pub struct ListFilter {
+    /// A parsed filter expression; [`Query::All`] when none was given.
+    pub query: Option<Query>,
+    pub all: bool,
+    pub open: bool,
+    pub done: bool,
+    /// Any of these statuses; takes precedence over `all`, `open` and `done`.
+    pub statuses: Vec<Status>,
+    pub priority: Option<Priority>,
+    pub tag: Option<String>,
+    /// Completed on or after this day.
+    pub since: Option<NaiveDate>,
+    /// Completed on or before this day.
+    pub until: Option<NaiveDate>,
+}
+
+impl ListFilter {
+    /// Without a status flag or a status in the expression only open todos
+    /// are shown, unless a completion window is given, which implies `all`.
+    /// Open todos always pass the window; closed ones pass only if they were
+    /// completed inside it, so cancelled todos never do.
+    pub fn to_query(&self) -> Query {
+        let mut filter = self.query.clone().unwrap_or(Query::All);
+        let window = self.since.is_some() || self.until.is_some();
+
+        let statuses = if !self.statuses.is_empty() {
+            self.statuses.clone()
+        } else if self.done {
+            vec![Status::Done]
+        } else if self.open {
+            Status::OPEN.to_vec()
+        } else if self.all || window || filter.mentions_status() {
+            Vec::new()
+        } else {
+            Status::OPEN.to_vec()
+        };
+        if !statuses.is_empty() {
+            filter = filter.and(Query::Term(Term::Status(statuses)));
+        }
+
+        if window {
+            let mut completed = Query::All;
+            if let Some(since) = self.since {
+                completed = completed.and(Query::Term(Term::Date(DateField::Completed, Cmp::Ge, since)));
+            }
+            if let Some(until) = self.until {
+                completed = completed.and(Query::Term(Term::Date(DateField::Completed, Cmp::Le, until)));
+            }
+            let open = Query::Term(Term::Status(Status::OPEN.to_vec()));
+            filter = filter.and(Query::Or(Box::new(open), Box::new(completed)));
+        }
+
+        if let Some(priority) = self.priority {
+            filter = filter.and(Query::Term(Term::Priority(Cmp::Eq, priority)));
+        }
+        if let Some(tag) = &self.tag {
+            filter = filter.and(Query::Term(Term::Tag(tag.clone())));
+        }
+        filter
+    }
+}
+
+fn rank(priority: Priority) -> u8 {
+    match priority {
+        Priority::Low => 1,
+        Priority::Medium => 2,
+        Priority::High => 3,
+    }
+}
+
+fn date_of(todo: &Todo, field: DateField) -> Option<NaiveDate> {
+    match field {
+        DateField::Due => todo.due_date.map(local_date),
+        DateField::Created => Some(todo.created_at.date_naive()),
+        DateField::Completed => todo.completed_at.map(|at| at.date_naive()),
+    }
+}
+
+#[derive(Debug)]
+struct Token {
+    text: String,
+    start: usize,
+    quoted: bool,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '(' || c == ')' {
+            chars.next();
+            tokens.push(Token { text: c.to_string(), start, quoted: false });
+        } else if c == '"' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '"')) => break,
+                    Some((_, c)) => text.push(c),
+                    None => return Err(query_error(input, start, "unterminated quote")),
+                }
+            }
+            tokens.push(Token { text, start, quoted: true });
+        } else {
+            let mut text = String::new();
+            while let Some(&(_, c)) = chars.peek() {
+                if c.is_whitespace() || c == '(' || c == ')' || c == '"' {
+                    break;
+                }
+                text.push(c);
+                chars.next();
+            }
+            tokens.push(Token { text, start, quoted: false });
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, position: usize, message: String) -> Error {
+        query_error(self.input, position, message)
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        self.tokens
+            .get(self.pos)
+            .is_some_and(|token| !token.quoted && token.text.eq_ignore_ascii_case(keyword))
+    }
+
+    fn parse_or(&mut self) -> Result<Query> {
+        let mut query = self.parse_and()?;
+        while self.peek_keyword("or") {
+            self.pos += 1;
+            query = Query::Or(Box::new(query), Box::new(self.parse_and()?));
+        }
+        Ok(query)
+    }
+
+    fn parse_and(&mut self) -> Result<Query> {
+        let mut query = self.parse_unary()?;
+        loop {
+            if self.peek_keyword("and") {
+                self.pos += 1;
+            } else if self.pos >= self.tokens.len() || self.peek_keyword("or") || self.tokens[self.pos].text == ")" {
+                break;
+            }
+            query = Query::And(Box::new(query), Box::new(self.parse_unary()?));
+        }
+        Ok(query)
+    }
+
+    fn parse_unary(&mut self) -> Result<Query> {
+        let token = match self.tokens.get(self.pos) {
+            Some(token) => token,
+            None => return Err(self.error(self.input.len(), "expected a term, found end of query".to_string())),
+        };
+        self.pos += 1;
+
+        if token.quoted {
+            return Ok(Query::Term(Term::Title(token.text.to_lowercase())));
+        }
+        match token.text.to_ascii_lowercase().as_str() {
+            "not" => Ok(Query::Not(Box::new(self.parse_unary()?))),
+            "(" => {
+                let open = token.start;
+                let query = self.parse_or()?;
+                match self.tokens.get(self.pos) {
+                    Some(token) if token.text == ")" => {
+                        self.pos += 1;
+                        Ok(query)
+                    }
+                    _ => Err(self.error(open, "unclosed '('".to_string())),
+                }
+            }
+            ")" | "and" | "or" => Err(self.error(token.start, format!("expected a term, found '{}'", token.text))),
+            _ => parse_term(&token.text).map(Query::Term).map_err(|message| self.error(token.start, message)),
+        }
+    }
+}
+
+fn parse_term(text: &str) -> std::result::Result<Term, String> {
+    let split = text.find([':', '<', '>', '=']);
+    let (field, op, value) = match split {
+        None => {
+            let word = text.to_ascii_lowercase();
+            return Ok(match status_names(&word) {
+                Some(statuses) => Term::Status(statuses),
+                None if word == "overdue" => Term::Overdue,
+                None => Term::Title(text.to_lowercase()),
+            })
+        }
+        Some(index) => {
+            let rest = &text[index..];
+            let (cmp, len) = match rest.as_bytes() {
+                [b'<', b'=', ..] => (Cmp::Le, 2),
+                [b'>', b'=', ..] => (Cmp::Ge, 2),
+                [b'<', ..] => (Cmp::Lt, 1),
+                [b'>', ..] => (Cmp::Gt, 1),
+                _ => (Cmp::Eq, 1),
+            };
+            (text[..index].to_ascii_lowercase(), cmp, &rest[len..])
+        }
+    };
+    if value.is_empty() {
+        return Err(format!("missing value after '{}'", &text[..text.len() - value.len()]));
+    }
+    let equality_only = |term: Term| match op {
+        Cmp::Eq => Ok(term),
+        _ => Err(format!("'{}' only supports ':'", field)),
+    };
+
+    match field.as_str() {
+        "id" => value.parse().map(|id| Term::Id(op, id)).map_err(|_| format!("invalid id '{}'", value)),
+        "title" => equality_only(Term::Title(value.to_lowercase())),
+        "tag" => equality_only(Term::Tag(value.to_string())),
+        "status" => equality_only(Term::Status(
+            status_names(value).ok_or_else(|| format!("unknown status '{}'", value))?,
+        )),
+        "priority" => Priority::from_name(value)
+            .map(|priority| Term::Priority(op, priority))
+            .ok_or_else(|| format!("unknown priority '{}' (expected high, medium or low)", value)),
+        "due" | "created" | "completed" => {
+            let field = match field.as_str() {
+                "due" => DateField::Due,
+                "created" => DateField::Created,
+                _ => DateField::Completed,
+            };
+            match (op, value) {
+                (Cmp::Eq, "none") => Ok(Term::HasDate(field, false)),
+                (Cmp::Eq, "any") => Ok(Term::HasDate(field, true)),
+                _ => parse_date(value).map(|date| Term::Date(field, op, date)).map_err(|_| {
+                    format!("invalid date '{}' (expected YYYY-MM-DD or today)", value)
+                }),
+            }
+        }
+        _ => Err(format!(
+            "unknown field '{}' (expected id, title, tag, status, priority, due, created or completed)",
+            field
+        )),
+    }
+}
+
+fn status_names(name: &str) -> Option<Vec<Status>> {
+    match name {
+        "open" => Some(Status::OPEN.to_vec()),
+        _ => Status::from_name(name).map(|status| vec![status]),
+    }
+}
+
+/// Parses the dates accepted in queries: `YYYY-MM-DD` or `today`.
+pub fn parse_date(value: &str) -> Result<NaiveDate> {
+    if value.eq_ignore_ascii_case("today") {
+        return Ok(Local::now().date_naive());
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| Error::Invalid(format!("Invalid date '{}' (expected YYYY-MM-DD or today)", value)))
+}
+
+fn query_error(input: &str, position: usize, message: impl Into<String>) -> Error {
+    Error::Query { input: input.to_string(), position: input[..position].chars().count(), message: message.into() }
+}
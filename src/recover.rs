@@ -0,0 +1,263 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Local, NaiveDateTime};
+use regex::Regex;
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+use crate::dates::{local_due, DueDate};
+use crate::recur::Recurrence;
+use crate::storage::Store;
+use crate::todo::{Priority, Status, Todo};
+
+/// Outcome of salvaging a data file that no longer deserializes.
+#[derive(Debug, Default)]
+pub struct Recovery {
+    /// Number of entries that were rebuilt into todos.
+    pub recovered: usize,
+    /// Number of entries that had nothing usable (no title) and were dropped.
+    pub skipped: usize,
+    /// Human-readable notes about every field that had to be defaulted.
+    pub warnings: Vec<String>,
+}
+
+/// Rebuilds as many todos as possible from `content`, field by field.
+///
+/// If the text is not valid JSON at all, every balanced `{...}` object that
+/// does parse is treated as a candidate entry.
+///
+/// `next_id` is kept past every id the text still mentions, including those of
+/// dropped entries, so ids of lost todos are not handed out again. Ids in the
+/// text whose entry no longer parses count as dropped.
+pub(crate) fn salvage(content: &str) -> (Store, Recovery) {
+    let mut recovery = Recovery::default();
+    let mut store = Store::default();
+
+    let entries = match serde_json::from_str::<Value>(content) {
+        Ok(Value::Array(entries)) => entries,
+        Ok(Value::Object(mut root)) => {
+            store.next_id = root.get("next_id").and_then(Value::as_u64).unwrap_or(0) as usize;
+            match root.remove("todos") {
+                Some(Value::Array(entries)) => entries,
+                _ => {
+                    recovery.warnings.push("no `todos` array found".to_string());
+                    Vec::new()
+                }
+            }
+        }
+        Ok(_) => {
+            recovery.warnings.push("top level is neither an array nor an object".to_string());
+            Vec::new()
+        }
+        Err(err) => {
+            recovery.warnings.push(format!("invalid JSON ({}); scanning for intact entries", err));
+            store.next_id = next_id_in_text(content);
+            let entries = scan_objects(content);
+            let intact: HashSet<u64> = entries.iter().filter_map(|entry| entry.get("id")?.as_u64()).collect();
+            let mut lost: Vec<usize> = numbers_in_text(content, "id").into_iter().filter(|&id| !intact.contains(&(id as u64))).collect();
+            lost.sort_unstable();
+            lost.dedup();
+            for id in &lost {
+                recovery.warnings.push(format!("entry with id {}: no longer valid JSON, dropped", id));
+            }
+            recovery.skipped += lost.len();
+            entries
+        }
+    };
+
+    for (index, entry) in entries.into_iter().enumerate() {
+        if let Some(id) = entry.get("id").and_then(Value::as_u64) {
+            store.next_id = store.next_id.max(id as usize + 1);
+        }
+        match entry {
+            Value::Object(fields) => match salvage_todo(index, &fields, &mut recovery.warnings) {
+                Some(todo) => {
+                    store.todos.push(todo);
+                    recovery.recovered += 1;
+                }
+                None => recovery.skipped += 1,
+            },
+            _ => {
+                recovery.warnings.push(format!("entry {}: not an object, dropped", index + 1));
+                recovery.skipped += 1;
+            }
+        }
+    }
+
+    (store, recovery)
+}
+
+fn salvage_todo(index: usize, fields: &Map<String, Value>, warnings: &mut Vec<String>) -> Option<Todo> {
+    let mut warn = |field: &str, action: &str| {
+        warnings.push(format!("entry {}: bad or missing `{}`, {}", index + 1, field, action));
+    };
+
+    let title = match fields.get("title").and_then(Value::as_str) {
+        Some(title) => title.to_string(),
+        None => {
+            warn("title", "dropped");
+            return None;
+        }
+    };
+
+    let id = fields.get("id").and_then(Value::as_u64).unwrap_or_else(|| {
+        warn("id", "a new id will be assigned");
+        0
+    }) as usize;
+
+    let uuid = fields
+        .get("uuid")
+        .and_then(Value::as_str)
+        .and_then(|s| Uuid::parse_str(s).ok());
+
+    let status = field_as::<Status>(fields, "status")
+        .or_else(|| match fields.get("completed").and_then(Value::as_bool)? {
+            true => Some(Status::Done),
+            false => Some(Status::Todo),
+        })
+        .unwrap_or_else(|| {
+            warn("status", "marked as todo");
+            Status::Todo
+        });
+
+    let created_at = field_as::<DateTime<Local>>(fields, "created_at").unwrap_or_else(|| {
+        warn("created_at", "set to now");
+        Local::now()
+    });
+
+    let completed_at = match fields.get("completed_at") {
+        None | Some(Value::Null) => None,
+        Some(_) => field_as::<DateTime<Local>>(fields, "completed_at").or_else(|| {
+            warn("completed_at", "cleared");
+            None
+        }),
+    };
+
+    let priority = field_as::<Priority>(fields, "priority")
+        .or_else(|| {
+            match fields.get("priority")?.as_str()?.to_lowercase().as_str() {
+                name @ ("high" | "medium" | "low") => Some(Priority::parse(Some(name))),
+                _ => None,
+            }
+        })
+        .unwrap_or_else(|| {
+            warn("priority", "set to low");
+            Priority::Low
+        });
+
+    let due_date = match fields.get("due_date") {
+        None | Some(Value::Null) => None,
+        Some(_) => field_as::<DueDate>(fields, "due_date")
+            .or_else(|| field_as::<NaiveDateTime>(fields, "due_date").map(local_due))
+            .or_else(|| {
+                warn("due_date", "cleared");
+                None
+            }),
+    };
+
+    let categories = match fields.get("categories") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Array(values)) => values.iter().filter_map(|v| v.as_str().map(String::from)).collect(),
+        Some(_) => {
+            warn("categories", "cleared");
+            Vec::new()
+        }
+    };
+
+    let recurrence = match fields.get("recurrence") {
+        None | Some(Value::Null) => None,
+        Some(_) => field_as::<Recurrence>(fields, "recurrence").or_else(|| {
+            warn("recurrence", "cleared");
+            None
+        }),
+    };
+
+    Some(Todo {
+        id,
+        uuid,
+        title,
+        status,
+        created_at,
+        completed_at,
+        priority,
+        due_zone: fields.get("due_zone").and_then(Value::as_str).map(String::from),
+        due_date,
+        categories,
+        recurrence,
+        parent_id: fields.get("parent_id").and_then(Value::as_u64).map(|id| id as usize),
+        depends_on: match fields.get("depends_on") {
+            Some(Value::Array(ids)) => ids.iter().filter_map(Value::as_u64).map(|id| id as usize).collect(),
+            _ => Vec::new(),
+        },
+        notes: fields.get("notes").and_then(Value::as_str).map(String::from).unwrap_or_default(),
+        checklist: field_as(fields, "checklist").unwrap_or_default(),
+    })
+}
+
+/// The lowest `next_id` consistent with damaged text: past its `next_id` and
+/// every `"id"` in it, whether or not the surrounding entry still parses.
+fn next_id_in_text(content: &str) -> usize {
+    let past_ids = numbers_in_text(content, "id").into_iter().map(|id| id.saturating_add(1));
+    numbers_in_text(content, "next_id").into_iter().chain(past_ids).max().unwrap_or(0)
+}
+
+/// Every whole number written as `"key": <number>` in `content`.
+fn numbers_in_text(content: &str, key: &str) -> Vec<usize> {
+    let pattern = Regex::new(&format!(r#""{}"\s*:\s*(\d+)"#, regex::escape(key))).unwrap();
+    pattern.captures_iter(content).filter_map(|found| found[1].parse().ok()).collect()
+}
+
+fn field_as<T: serde::de::DeserializeOwned>(fields: &Map<String, Value>, name: &str) -> Option<T> {
+    fields.get(name).and_then(|value| serde_json::from_value(value.clone()).ok())
+}
+
+/// Collects every balanced `{...}` span that parses as a JSON object and looks
+/// like a todo, skipping the `{ "next_id": .., "todos": [..] }` wrapper itself.
+fn scan_objects(content: &str) -> Vec<Value> {
+    let bytes = content.as_bytes();
+    let mut objects = Vec::new();
+    let mut start = 0;
+    while let Some(offset) = content[start..].find('{') {
+        let open = start + offset;
+        match matching_brace(bytes, open) {
+            Some(close) => match serde_json::from_str::<Value>(&content[open..=close]) {
+                Ok(value) if value.get("title").is_some() => {
+                    objects.push(value);
+                    start = close + 1;
+                }
+                _ => start = open + 1,
+            },
+            None => start = open + 1,
+        }
+    }
+    objects
+}
+
+fn matching_brace(bytes: &[u8], open: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, &b) in bytes.iter().enumerate().skip(open) {
+        if in_string {
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match b {
+            b'"' => in_string = true,
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
@@ -0,0 +1,267 @@
+//! Recurring tasks.
+//!
+//! A [`Recurrence`] is stored as an RRULE-style string, e.g.
+//! `FREQ=WEEKLY;INTERVAL=1;BYDAY=MO,TH`. Rules that count from the completion
+//! date instead of the due date carry the extension `X-FROM=COMPLETION`.
+
+use std::fmt;
+
+use chrono::{DateTime, Datelike, Days, Duration, Local, Months, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::dates::{self, local_due, DueDate};
+use crate::error::{Error, Result};
+
+/// How a recurring todo repeats. Completing it spawns the next occurrence.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Recurrence {
+    /// Every `interval` days after the previous due date.
+    Daily { interval: u32 },
+    /// Every `interval` weeks on `weekdays`; empty means the due date's weekday.
+    Weekly { interval: u32, weekdays: Vec<Weekday> },
+    /// Every `interval` months on `day`, clamped to shorter months; `None`
+    /// means the due date's day.
+    Monthly { interval: u32, day: Option<u32> },
+    /// `days` after the task was actually completed.
+    AfterCompletion { days: u32 },
+}
+
+impl Recurrence {
+    /// Parses either an RRULE (`FREQ=MONTHLY;BYMONTHDAY=15`) or one of the
+    /// shorthands `daily`, `weekly`, `monthly`, `every 3 days`, `every 2 weeks on
+    /// mon,thu`, `every friday`, `monthly on 15` and `every 10 days after done`.
+    /// Everything [`Recurrence::describe`] prints parses back.
+    pub fn parse(input: &str) -> Result<Self> {
+        let input = input.trim();
+        let rule = if input.contains('=') { parse_rrule(input) } else { parse_words(&input.to_lowercase()) };
+        rule.ok_or_else(|| {
+            Error::Invalid(format!(
+                "Could not understand recurrence '{}'; try daily, weekly on mon,thu, monthly on 15, every 3 days or every 10 days after done",
+                input
+            ))
+        })
+    }
+
+    /// Fills in the weekday or day of month a rule left open from `base`, so
+    /// later occurrences do not drift when a month is short.
+    pub fn anchored(&self, base: NaiveDate) -> Recurrence {
+        match self {
+            Recurrence::Weekly { interval, weekdays } if weekdays.is_empty() => {
+                Recurrence::Weekly { interval: *interval, weekdays: vec![base.weekday()] }
+            }
+            Recurrence::Monthly { interval, day: None } => Recurrence::Monthly { interval: *interval, day: Some(base.day()) },
+            rule => rule.clone(),
+        }
+    }
+
+    /// The due date of the occurrence after one due at `due` and completed at
+    /// `completed_at`. Without a due date the schedule counts from the day of
+    /// completion. The time of day is kept. Fails if that date is out of range.
+    pub fn next_due(&self, due: Option<DueDate>, completed_at: DateTime<Local>) -> Result<DueDate> {
+        let (base, time) = match due {
+            Some(due) => {
+                let local = due.with_timezone(&Local).naive_local();
+                (local.date(), local.time())
+            }
+            None => (completed_at.date_naive(), dates::end_of_day()),
+        };
+
+        let date = match self.anchored(base) {
+            Recurrence::Daily { interval } => base.checked_add_days(Days::new(u64::from(interval))),
+            Recurrence::Weekly { interval, weekdays } => next_weekly(base, interval, &weekdays),
+            Recurrence::Monthly { interval, day } => {
+                base.with_day(1).unwrap().checked_add_months(Months::new(interval)).and_then(|month| {
+                    let last = dates::last_day_of_month(month)?.day();
+                    month.with_day(day.unwrap_or(1).min(last))
+                })
+            }
+            Recurrence::AfterCompletion { days } => completed_at.date_naive().checked_add_days(Days::new(u64::from(days))),
+        };
+        let date = date.ok_or_else(|| Error::Invalid(format!("The next occurrence of '{}' is too far in the future; change it with `edit --repeat` or `--no-repeat`", self.describe())))?;
+        Ok(local_due(date.and_time(time)))
+    }
+
+    /// A short human description for listings, e.g. `weekly on mon, thu`.
+    pub fn describe(&self) -> String {
+        match self {
+            Recurrence::Daily { interval: 1 } => "daily".to_string(),
+            Recurrence::Daily { interval } => format!("every {} days", interval),
+            Recurrence::Weekly { interval, weekdays } => {
+                let every = if *interval == 1 { "weekly".to_string() } else { format!("every {} weeks", interval) };
+                if weekdays.is_empty() {
+                    every
+                } else {
+                    let days: Vec<String> = weekdays.iter().map(|day| day.to_string().to_lowercase()).collect();
+                    format!("{} on {}", every, days.join(", "))
+                }
+            }
+            Recurrence::Monthly { interval, day } => {
+                let every = if *interval == 1 { "monthly".to_string() } else { format!("every {} months", interval) };
+                match day {
+                    Some(day) => format!("{} on day {}", every, day),
+                    None => every,
+                }
+            }
+            Recurrence::AfterCompletion { days: 1 } => "1 day after done".to_string(),
+            Recurrence::AfterCompletion { days } => format!("{} days after done", days),
+        }
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Recurrence::Daily { interval } => write!(f, "FREQ=DAILY;INTERVAL={}", interval),
+            Recurrence::Weekly { interval, weekdays } => {
+                write!(f, "FREQ=WEEKLY;INTERVAL={}", interval)?;
+                if !weekdays.is_empty() {
+                    let days: Vec<&str> = weekdays.iter().map(|&day| rrule_day(day)).collect();
+                    write!(f, ";BYDAY={}", days.join(","))?;
+                }
+                Ok(())
+            }
+            Recurrence::Monthly { interval, day } => {
+                write!(f, "FREQ=MONTHLY;INTERVAL={}", interval)?;
+                if let Some(day) = day {
+                    write!(f, ";BYMONTHDAY={}", day)?;
+                }
+                Ok(())
+            }
+            Recurrence::AfterCompletion { days } => write!(f, "FREQ=DAILY;INTERVAL={};X-FROM=COMPLETION", days),
+        }
+    }
+}
+
+impl TryFrom<String> for Recurrence {
+    type Error = Error;
+
+    fn try_from(rule: String) -> Result<Self> {
+        Recurrence::parse(&rule)
+    }
+}
+
+impl From<Recurrence> for String {
+    fn from(rule: Recurrence) -> String {
+        rule.to_string()
+    }
+}
+
+/// The first of `weekdays` after `base` in the same week, or else the first of
+/// them `interval` weeks on. Weeks start on Monday. `None` if that is out of
+/// range.
+fn next_weekly(base: NaiveDate, interval: u32, weekdays: &[Weekday]) -> Option<NaiveDate> {
+    let offset = i64::from(base.weekday().num_days_from_monday());
+    let monday = base - Duration::days(offset);
+    if let Some(date) = (offset + 1..7).map(|day| monday + Duration::days(day)).find(|date| weekdays.contains(&date.weekday())) {
+        return Some(date);
+    }
+    let later_monday = monday.checked_add_days(Days::new(7 * u64::from(interval)))?;
+    (0..7).filter_map(|day| later_monday.checked_add_days(Days::new(day))).find(|date| weekdays.contains(&date.weekday()))
+}
+
+fn rrule_day(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn weekday_of(code: &str) -> Option<Weekday> {
+    [Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri, Weekday::Sat, Weekday::Sun]
+        .into_iter()
+        .find(|&day| rrule_day(day).eq_ignore_ascii_case(code))
+}
+
+fn parse_rrule(input: &str) -> Option<Recurrence> {
+    let mut freq = None;
+    let mut interval = 1;
+    let mut weekdays = Vec::new();
+    let mut day = None;
+    let mut from_completion = false;
+
+    for part in input.split(';').filter(|part| !part.is_empty()) {
+        let (key, value) = part.split_once('=')?;
+        match key.trim().to_uppercase().as_str() {
+            "FREQ" => freq = Some(value.trim().to_uppercase()),
+            "INTERVAL" => interval = value.trim().parse().ok()?,
+            "BYDAY" => weekdays = value.split(',').map(|code| weekday_of(code.trim())).collect::<Option<_>>()?,
+            "BYMONTHDAY" => day = Some(value.trim().parse().ok()?),
+            "X-FROM" if value.trim().eq_ignore_ascii_case("COMPLETION") => from_completion = true,
+            _ => return None,
+        }
+    }
+
+    let rule = match freq?.as_str() {
+        "DAILY" if from_completion => Recurrence::AfterCompletion { days: interval },
+        "DAILY" => Recurrence::Daily { interval },
+        "WEEKLY" => Recurrence::Weekly { interval, weekdays },
+        "MONTHLY" => Recurrence::Monthly { interval, day },
+        _ => return None,
+    };
+    valid(rule)
+}
+
+fn parse_words(input: &str) -> Option<Recurrence> {
+    let (input, from_completion) = match input
+        .strip_suffix("after done")
+        .or_else(|| input.strip_suffix("after completion"))
+    {
+        Some(rest) => (rest.trim(), true),
+        None => (input, false),
+    };
+    let (every, on) = match input.split_once(" on ") {
+        Some((every, on)) => (every.trim(), Some(on.trim())),
+        None => (input, None),
+    };
+
+    let words: Vec<&str> = every.split_whitespace().collect();
+    let (interval, unit) = match words.as_slice() {
+        ["daily"] => (1, "day"),
+        ["weekly"] => (1, "week"),
+        ["monthly"] => (1, "month"),
+        ["every", unit] => (1, *unit),
+        ["every", count, unit] | [count, unit] => (count.parse().ok()?, *unit),
+        _ => return None,
+    };
+
+    let rule = match (unit.trim_end_matches('s'), on) {
+        ("day", None) if from_completion => Recurrence::AfterCompletion { days: interval },
+        ("day", None) => Recurrence::Daily { interval },
+        ("week", on) if !from_completion => {
+            let weekdays = match on {
+                Some(days) => days.split(',').map(|day| dates::weekday(day.trim())).collect::<Option<_>>()?,
+                None => Vec::new(),
+            };
+            Recurrence::Weekly { interval, weekdays }
+        }
+        ("month", on) if !from_completion => {
+            let day = match on {
+                Some(day) => Some(day.trim_start_matches("the ").trim_start_matches("day ").trim_end_matches(char::is_alphabetic).parse().ok()?),
+                None => None,
+            };
+            Recurrence::Monthly { interval, day }
+        }
+        // `every monday` or `every mon,thu`
+        (_, None) if !from_completion && interval == 1 => Recurrence::Weekly {
+            interval,
+            weekdays: unit.split(',').map(|day| dates::weekday(day.trim())).collect::<Option<_>>()?,
+        },
+        _ => return None,
+    };
+    valid(rule)
+}
+
+fn valid(rule: Recurrence) -> Option<Recurrence> {
+    let ok = match &rule {
+        Recurrence::Daily { interval } | Recurrence::Weekly { interval, .. } => *interval > 0,
+        Recurrence::Monthly { interval, day } => *interval > 0 && day.is_none_or(|day| (1..=31).contains(&day)),
+        Recurrence::AfterCompletion { days } => *days > 0,
+    };
+    ok.then_some(rule)
+}
@@ -0,0 +1,106 @@
+//! Versioning of the on-disk format.
+//!
+//! Every file written today is an envelope `{ "schema_version": N, ... }`.
+//! Older files are upgraded on load by running one migration per version
+//! until they reach [`CURRENT_VERSION`]:
+//!
+//! | version | layout                                        |
+//! |---------|-----------------------------------------------|
+//! | 1       | bare array of todos                           |
+//! | 2       | `{ "next_id", "todos" }` without a version    |
+//! | 3       | `{ "schema_version", "next_id", "todos" }`    |
+//! | 4       | todos have a `status` instead of `completed`  |
+//! | 5       | `due_date` carries a UTC offset               |
+
+use chrono::NaiveDateTime;
+use serde_json::{json, Map, Value};
+
+use crate::dates::local_due;
+
+use crate::error::{Error, Result};
+
+/// The version written by this build.
+pub const CURRENT_VERSION: u32 = 5;
+
+/// Migrations indexed by the version they upgrade from, starting at version 1.
+const MIGRATIONS: [fn(Value) -> Value; (CURRENT_VERSION - 1) as usize] = [v1_to_v2, v2_to_v3, v3_to_v4, v4_to_v5];
+
+/// Works out which schema version a parsed file uses. Returns 0 for shapes no
+/// version ever produced.
+pub fn detect_version(root: &Value) -> u32 {
+    match root {
+        Value::Array(_) => 1,
+        Value::Object(fields) => match fields.get("schema_version") {
+            Some(version) => version.as_u64().map_or(0, |v| v as u32),
+            None => 2,
+        },
+        _ => 0,
+    }
+}
+
+/// Applies the single migration that upgrades `root` from its current version.
+/// A file already at [`CURRENT_VERSION`] is returned unchanged.
+pub fn migrate_once(root: Value) -> Result<Value> {
+    let version = detect_version(&root);
+    match version {
+        CURRENT_VERSION => Ok(root),
+        1..CURRENT_VERSION => Ok(MIGRATIONS[version as usize - 1](root)),
+        _ => Err(Error::UnsupportedSchema(version)),
+    }
+}
+
+/// Upgrades `root` step by step to [`CURRENT_VERSION`].
+pub fn migrate(mut root: Value) -> Result<Value> {
+    while detect_version(&root) != CURRENT_VERSION {
+        root = migrate_once(root)?;
+    }
+    Ok(root)
+}
+
+/// Wraps the bare array in an object so the id counter has somewhere to live.
+/// `next_id` starts at 0 and is moved past the highest id when the list loads.
+fn v1_to_v2(root: Value) -> Value {
+    json!({ "next_id": 0, "todos": root })
+}
+
+/// Adds the explicit version marker.
+fn v2_to_v3(root: Value) -> Value {
+    let mut fields = match root {
+        Value::Object(fields) => fields,
+        _ => Map::new(),
+    };
+    fields.entry("next_id").or_insert(json!(0));
+    fields.entry("todos").or_insert(json!([]));
+
+    let mut envelope = Map::new();
+    envelope.insert("schema_version".to_string(), json!(3));
+    envelope.extend(fields);
+    Value::Object(envelope)
+}
+
+/// Replaces the `completed` flag with a `status` of `Done` or `Todo`.
+fn v3_to_v4(mut root: Value) -> Value {
+    if let Some(todos) = root.get_mut("todos").and_then(Value::as_array_mut) {
+        for todo in todos.iter_mut().filter_map(Value::as_object_mut) {
+            let done = todo.remove("completed").and_then(|c| c.as_bool()).unwrap_or(false);
+            todo.insert("status".to_string(), json!(if done { "Done" } else { "Todo" }));
+        }
+    }
+    root["schema_version"] = json!(4);
+    root
+}
+
+/// Pins naive due dates to the local time zone of whoever runs the upgrade,
+/// which is the zone they were written in for all but shared files.
+fn v4_to_v5(mut root: Value) -> Value {
+    if let Some(todos) = root.get_mut("todos").and_then(Value::as_array_mut) {
+        for todo in todos.iter_mut().filter_map(Value::as_object_mut) {
+            let naive = todo.get("due_date").and_then(Value::as_str).and_then(|due| due.parse::<NaiveDateTime>().ok());
+            if let Some(naive) = naive {
+                todo.insert("due_date".to_string(), json!(local_due(naive).to_rfc3339()));
+            }
+        }
+    }
+    root["schema_version"] = json!(5);
+    root
+}
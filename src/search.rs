@@ -0,0 +1,219 @@
+//! Ranked search over titles, notes and tags.
+//!
+//! Fuzzy searches split the query into words; every word has to turn up in
+//! some field, as a whole word, a word with the same English stem, a prefix, a
+//! substring or, for longer words, with a typo or two. Matches in titles count
+//! more than tags, and tags more than notes. Regex searches score by the number
+//! of matches instead.
+
+use std::cmp::Reverse;
+
+use regex::{Regex, RegexBuilder};
+use rust_stemmers::{Algorithm, Stemmer};
+
+use crate::error::{Error, Result};
+use crate::todo::Todo;
+
+/// Where in a todo a match was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Title,
+    /// The tag at this index of `categories`.
+    Tag(usize),
+    Notes,
+}
+
+impl Field {
+    fn weight(&self) -> u32 {
+        match self {
+            Field::Title => 3,
+            Field::Tag(_) => 2,
+            Field::Notes => 1,
+        }
+    }
+}
+
+/// A matched byte range within one field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub field: Field,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A todo that matched, with its score (higher is better) and what matched.
+#[derive(Debug, Clone)]
+pub struct Hit<'a> {
+    pub todo: &'a Todo,
+    pub score: u32,
+    pub spans: Vec<Span>,
+}
+
+impl Hit<'_> {
+    /// The matched ranges in `field`, in order.
+    pub fn spans_in(&self, field: Field) -> Vec<(usize, usize)> {
+        let mut ranges: Vec<(usize, usize)> =
+            self.spans.iter().filter(|span| span.field == field).map(|span| (span.start, span.end)).collect();
+        ranges.sort_unstable();
+        ranges.dedup();
+        ranges
+    }
+}
+
+/// A compiled search, run with [`Search::run`] or [`TodoList::find`](crate::TodoList::find).
+#[derive(Debug, Clone)]
+pub enum Search {
+    Fuzzy(Vec<String>),
+    Regex(Regex),
+}
+
+impl Search {
+    /// A typo-tolerant search for every word of `query`, ignoring case.
+    pub fn fuzzy(query: &str) -> Self {
+        Search::Fuzzy(words(query).into_iter().map(|(_, _, word)| word).collect())
+    }
+
+    /// A case-insensitive regular expression search; `(?-i)` makes it case-sensitive.
+    pub fn regex(pattern: &str) -> Result<Self> {
+        RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+            .map(Search::Regex)
+            .map_err(|err| Error::Invalid(format!("Invalid regex: {}", err)))
+    }
+
+    /// Scores every todo and returns the matches, best first. Equal scores keep
+    /// list order. An empty fuzzy query matches nothing.
+    pub fn run<'a>(&self, todos: &'a [Todo]) -> Vec<Hit<'a>> {
+        self.run_over(todos.iter())
+    }
+
+    /// [`Search::run`] over just `todos`, e.g. the candidates picked by an index.
+    pub(crate) fn run_over<'a>(&self, todos: impl Iterator<Item = &'a Todo>) -> Vec<Hit<'a>> {
+        let stemmer = Stemmer::create(Algorithm::English);
+        let stems: Vec<String> = match self {
+            Search::Fuzzy(terms) => terms.iter().map(|term| stemmer.stem(term).into_owned()).collect(),
+            Search::Regex(_) => Vec::new(),
+        };
+        let mut hits: Vec<Hit> = todos.filter_map(|todo| self.score(todo, &stemmer, &stems)).collect();
+        hits.sort_by_key(|hit| Reverse(hit.score));
+        hits
+    }
+
+    fn score<'a>(&self, todo: &'a Todo, stemmer: &Stemmer, stems: &[String]) -> Option<Hit<'a>> {
+        let fields = fields(todo);
+        let mut hit = Hit { todo, score: 0, spans: Vec::new() };
+        match self {
+            Search::Fuzzy(terms) if terms.is_empty() => return None,
+            Search::Fuzzy(terms) => {
+                for (term, stem) in terms.iter().zip(stems) {
+                    let mut best = 0;
+                    for (field, text) in &fields {
+                        for (start, end, word) in words(text) {
+                            let quality = quality(term, &word).max(if stemmer.stem(&word) == *stem { 90 } else { 0 });
+                            if quality == 0 {
+                                continue;
+                            }
+                            hit.spans.push(Span { field: *field, start, end });
+                            best = best.max(quality * field.weight());
+                        }
+                    }
+                    if best == 0 {
+                        return None;
+                    }
+                    hit.score += best;
+                }
+            }
+            Search::Regex(regex) => {
+                for (field, text) in &fields {
+                    for found in regex.find_iter(text).filter(|found| !found.is_empty()) {
+                        hit.spans.push(Span { field: *field, start: found.start(), end: found.end() });
+                        hit.score += 10 * field.weight();
+                    }
+                }
+                if hit.spans.is_empty() {
+                    return None;
+                }
+            }
+        }
+        Some(hit)
+    }
+}
+
+fn fields(todo: &Todo) -> Vec<(Field, &str)> {
+    let mut fields = vec![(Field::Title, todo.title.as_str())];
+    fields.extend(todo.categories.iter().enumerate().map(|(index, tag)| (Field::Tag(index), tag.as_str())));
+    fields.push((Field::Notes, todo.notes.as_str()));
+    fields
+}
+
+/// How well `term` matches `word`, both lowercase: 100 for the same word, 80
+/// for a prefix, 60 for a substring, and less for near misses; 0 for none.
+/// Words sharing a stem score 90, which callers check separately.
+pub(crate) fn quality(term: &str, word: &str) -> u32 {
+    if word == term {
+        return 100;
+    }
+    if word.starts_with(term) {
+        return 80;
+    }
+    if term.chars().count() >= 3 && word.contains(term) {
+        return 60;
+    }
+
+    let allowed = match term.chars().count() {
+        0..=3 => return 0,
+        4..=6 => 1,
+        _ => 2,
+    };
+    // Compare against the whole word, and against a prefix of the term's length
+    // at one extra cost, so `mangement` finds `management` and `projetc` still
+    // finds `projections`.
+    let prefix: String = word.chars().take(term.chars().count()).collect();
+    let distance = edit_distance(term, word).min(edit_distance(term, &prefix) + 1);
+    if distance <= allowed {
+        50 - 10 * distance as u32
+    } else {
+        0
+    }
+}
+
+/// Splits `text` into lowercase alphanumeric words with their byte ranges.
+pub(crate) fn words(text: &str) -> Vec<(usize, usize, String)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (index, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(index),
+            (false, Some(begin)) => {
+                words.push((begin, index, text[begin..index].to_lowercase()));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+/// Optimal string alignment distance: insertions, deletions, substitutions
+/// and swaps of adjacent characters each cost one.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    rows[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1).min(rows[i][j - 1] + 1).min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
@@ -0,0 +1,114 @@
+//! `todo show`: everything about one task, including its notes and checklist.
+
+use colored::*;
+use todo_list::{Todo, TodoList};
+
+use crate::display_todo;
+use crate::style::{colors, symbols};
+
+pub fn show(todo_list: &TodoList, todo: &Todo) {
+    println!();
+    display_todo(&todo_list.overview(), todo, 0);
+    println!("{}", "=".repeat(50));
+
+    if let Some(uuid) = todo.uuid {
+        println!("{} {}", colors().label.paint("uuid:"), colors().muted.paint(&uuid.to_string()));
+    }
+    if let Some(parent) = todo.parent_id.and_then(|id| todo_list.get(id)) {
+        println!("{} [{}] {}", colors().label.paint("parent:"), colors().accent.paint(&parent.id.to_string()), parent.title);
+    }
+
+    let subtasks: Vec<&Todo> = todo_list.todos().iter().filter(|child| child.parent_id == Some(todo.id)).collect();
+    if !subtasks.is_empty() {
+        println!("\n{}", colors().heading.paint("Subtasks"));
+        for child in subtasks {
+            let mark = if child.is_done() {
+                colors().done.paint(&symbols().done)
+            } else if child.status.is_open() {
+                colors().todo.paint(&symbols().todo)
+            } else {
+                colors().cancelled.paint(&symbols().cancelled)
+            };
+            println!("  {} [{}] {}", mark, colors().accent.paint(&child.id.to_string()), child.title);
+        }
+    }
+
+    if !todo.checklist.is_empty() {
+        println!("\n{}", colors().heading.paint("Checklist"));
+        for (number, item) in todo.checklist.iter().enumerate().map(|(index, item)| (index + 1, item)) {
+            if item.done {
+                println!("  {:>2}. {} {}", number, colors().done.paint(&symbols().checked), colors().muted.paint(&item.text));
+            } else {
+                println!("  {:>2}. {} {}", number, colors().todo.paint(&symbols().unchecked), item.text);
+            }
+        }
+    }
+
+    if !todo.notes.is_empty() {
+        println!("\n{}", colors().heading.paint("Notes"));
+        for line in render_markdown(&todo.notes) {
+            println!("  {}", line);
+        }
+    }
+    println!();
+}
+
+/// Renders the Markdown that turns up in notes for the terminal: headings,
+/// bullets, task-list items, quotes, code fences, `**bold**` and `code`.
+fn render_markdown(text: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut in_code = false;
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            lines.push(format!("    {}", colors().muted.paint(line)));
+            continue;
+        }
+
+        let trimmed = line.trim_start();
+        let indent = &line[..line.len() - trimmed.len()];
+        let rendered = if let Some(heading) = trimmed.strip_prefix('#') {
+            inline(heading.trim_start_matches('#').trim()).bold().underline().to_string()
+        } else if let Some(item) = trimmed.strip_prefix("- [ ] ").or_else(|| trimmed.strip_prefix("* [ ] ")) {
+            format!("{}{} {}", indent, colors().todo.paint(&symbols().unchecked), inline(item))
+        } else if let Some(item) = ["- [x] ", "- [X] ", "* [x] ", "* [X] "].iter().find_map(|prefix| trimmed.strip_prefix(prefix)) {
+            format!("{}{} {}", indent, colors().done.paint(&symbols().checked), inline(item))
+        } else if let Some(item) = trimmed.strip_prefix("- ").or_else(|| trimmed.strip_prefix("* ")) {
+            format!("{}{} {}", indent, symbols().bullet, inline(item))
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            format!("{}{} {}", indent, colors().muted.paint(&symbols().quote), inline(quote.trim_start()).italic())
+        } else {
+            format!("{}{}", indent, inline(trimmed))
+        };
+        lines.push(rendered);
+    }
+    lines
+}
+
+/// Styles `**bold**` and `code` spans; unmatched markers are left as typed.
+fn inline(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    loop {
+        let bold = rest.find("**");
+        let code = rest.find('`');
+        let (start, marker) = match (bold, code) {
+            (Some(b), Some(c)) if c < b => (c, "`"),
+            (Some(b), _) => (b, "**"),
+            (None, Some(c)) => (c, "`"),
+            (None, None) => break,
+        };
+        let after = &rest[start + marker.len()..];
+        let Some(end) = after.find(marker) else { break };
+        out.push_str(&rest[..start]);
+        let span = &after[..end];
+        let styled = if marker == "`" { colors().accent.paint(span) } else { span.bold() };
+        out.push_str(&styled.to_string());
+        rest = &after[end + marker.len()..];
+    }
+    out.push_str(rest);
+    out
+}
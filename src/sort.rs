@@ -0,0 +1,111 @@
+use std::cmp::Ordering;
+
+use crate::error::{Error, Result};
+use crate::todo::{Priority, Todo};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortField {
+    Id,
+    Title,
+    /// Most urgent first when ascending.
+    Priority,
+    Due,
+    Created,
+    Completed,
+}
+
+/// One key of a multi-key sort. Todos missing the field (no due date, not
+/// completed) always sort after those that have it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SortKey {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+impl SortKey {
+    /// Parses a comma-separated list such as `priority,-due` or `due:desc,title`.
+    /// A leading `-` or a `:desc` suffix sorts that key in descending order.
+    pub fn parse_list(spec: &str) -> Result<Vec<SortKey>> {
+        spec.split(',').map(str::trim).filter(|key| !key.is_empty()).map(SortKey::parse).collect()
+    }
+
+    pub fn parse(key: &str) -> Result<SortKey> {
+        let (name, mut descending) = match key.strip_prefix('-') {
+            Some(name) => (name, true),
+            None => (key, false),
+        };
+        let name = match name.rsplit_once(':') {
+            Some((name, "desc")) => {
+                descending = !descending;
+                name
+            }
+            Some((name, "asc")) => name,
+            Some((_, direction)) => {
+                return Err(Error::Invalid(format!("Unknown sort direction '{}' (expected asc or desc)", direction)))
+            }
+            None => name,
+        };
+        let field = match name {
+            "id" => SortField::Id,
+            "title" => SortField::Title,
+            "priority" => SortField::Priority,
+            "due" => SortField::Due,
+            "created" => SortField::Created,
+            "completed" => SortField::Completed,
+            _ => {
+                return Err(Error::Invalid(format!(
+                    "Unknown sort key '{}' (expected id, title, priority, due, created or completed)",
+                    name
+                )))
+            }
+        };
+        Ok(SortKey { field, descending })
+    }
+
+    fn compare(&self, a: &Todo, b: &Todo) -> Ordering {
+        let ordering = match self.field {
+            SortField::Id => a.id.cmp(&b.id),
+            SortField::Title => a.title.to_lowercase().cmp(&b.title.to_lowercase()),
+            SortField::Priority => urgency(a.priority).cmp(&urgency(b.priority)),
+            SortField::Due => return self.compare_optional(a.due_date, b.due_date),
+            SortField::Created => a.created_at.cmp(&b.created_at),
+            SortField::Completed => return self.compare_optional(a.completed_at, b.completed_at),
+        };
+        self.direct(ordering)
+    }
+
+    fn compare_optional<T: Ord>(&self, a: Option<T>, b: Option<T>) -> Ordering {
+        match (a, b) {
+            (Some(a), Some(b)) => self.direct(a.cmp(&b)),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+
+    fn direct(&self, ordering: Ordering) -> Ordering {
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+fn urgency(priority: Priority) -> u8 {
+    match priority {
+        Priority::High => 0,
+        Priority::Medium => 1,
+        Priority::Low => 2,
+    }
+}
+
+/// Sorts by each key in turn; ties keep their list order.
+pub fn sort_todos(todos: &mut [&Todo], keys: &[SortKey]) {
+    todos.sort_by(|a, b| {
+        keys.iter()
+            .map(|key| key.compare(a, b))
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    });
+}
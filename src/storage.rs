@@ -0,0 +1,85 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::error::Result;
+use crate::schema::{self, CURRENT_VERSION};
+use crate::todo::Todo;
+
+/// The on-disk layout of todos.json, at [`CURRENT_VERSION`].
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct Store {
+    pub schema_version: u32,
+    pub next_id: usize,
+    pub todos: Vec<Todo>,
+}
+
+impl Default for Store {
+    fn default() -> Self {
+        Store { schema_version: CURRENT_VERSION, next_id: 0, todos: Vec::new() }
+    }
+}
+
+/// An id that was reassigned because it collided with an earlier todo.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IdRepair {
+    pub old_id: usize,
+    pub new_id: usize,
+}
+
+impl Store {
+    /// Parses a file of any known schema version, migrating it to the current
+    /// one. Also returns the version the file was written with.
+    ///
+    /// Current files are deserialized straight from the text so that errors keep
+    /// their line and column.
+    pub fn from_json(content: &str) -> Result<(Self, u32)> {
+        let root: Value = serde_json::from_str(content)?;
+        let version = schema::detect_version(&root);
+        let store = if version == CURRENT_VERSION {
+            serde_json::from_str(content)?
+        } else {
+            serde_json::from_value(schema::migrate(root)?)?
+        };
+        Ok((store, version))
+    }
+
+    pub fn allocate_id(&mut self) -> usize {
+        self.next_id = self.next_id.max(1);
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    /// Gives every duplicate or zero id a fresh one, backfills missing uuids and
+    /// moves `next_id` past every id in use. The first todo holding an id keeps it.
+    pub fn repair(&mut self) -> (Vec<IdRepair>, bool) {
+        let max_id = self.todos.iter().map(|t| t.id).max().unwrap_or(0);
+        let mut changed = false;
+        if self.next_id <= max_id {
+            self.next_id = max_id + 1;
+            changed = true;
+        }
+
+        let mut seen = HashSet::new();
+        let mut repairs = Vec::new();
+        for index in 0..self.todos.len() {
+            let old_id = self.todos[index].id;
+            if old_id == 0 || !seen.insert(old_id) {
+                let new_id = self.allocate_id();
+                self.todos[index].id = new_id;
+                seen.insert(new_id);
+                repairs.push(IdRepair { old_id, new_id });
+            }
+            if self.todos[index].uuid.is_none() {
+                self.todos[index].uuid = Some(Uuid::new_v4());
+                changed = true;
+            }
+        }
+
+        changed |= !repairs.is_empty();
+        (repairs, changed)
+    }
+}
@@ -0,0 +1,42 @@
+//! Whether output is colored, and the theme it is drawn with.
+
+use std::sync::OnceLock;
+
+use colored::ColoredString;
+use todo_list::theme::{Colors, Symbols};
+use todo_list::Theme;
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Picks the theme for the rest of the run; the first call wins.
+pub fn init(theme: Theme) {
+    let _ = THEME.set(theme);
+}
+
+/// The theme picked by [`init`], the default one if it was never called.
+pub fn theme() -> &'static Theme {
+    THEME.get_or_init(Theme::default)
+}
+
+pub fn colors() -> &'static Colors {
+    &theme().colors
+}
+
+pub fn symbols() -> &'static Symbols {
+    &theme().symbols
+}
+
+/// The success mark, e.g. a green `✓`.
+pub fn ok() -> ColoredString {
+    colors().ok.paint(&symbols().ok)
+}
+
+/// The warning mark, `!`.
+pub fn warn() -> ColoredString {
+    colors().warn.paint("!")
+}
+
+/// The failure mark, e.g. a red `✗`.
+pub fn error() -> ColoredString {
+    colors().error.paint(&symbols().error)
+}
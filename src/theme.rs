@@ -0,0 +1,357 @@
+//! Colors, symbols and visible fields of the command-line output.
+//!
+//! A [`Theme`] starts from one of the bundled themes and is adjusted by the
+//! `[theme]` table of the [config file](crate::config):
+//!
+//! ```toml
+//! [theme]
+//! base = "high-contrast"
+//! fields = ["priority", "due", "tags"]
+//!
+//! [theme.colors]
+//! accent = "bright magenta bold"
+//! highlight = "black on bright green"
+//!
+//! [theme.symbols]
+//! done = "✔"
+//! ```
+
+use std::env;
+use std::ffi::OsString;
+use std::fmt;
+use std::io::{self, IsTerminal};
+
+use colored::{Color, ColoredString, Colorize};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// Names of the bundled themes, usable as `base` in the config file.
+pub const BUNDLED: &[&str] = &["default", "high-contrast", "monochrome"];
+
+/// The `--color` setting.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(Error::Invalid(format!("Unknown color setting '{}' (expected auto, always or never)", name))),
+        }
+    }
+
+    /// `--color=always|never` wins; otherwise a non-empty `NO_COLOR` turns colors
+    /// off, `CLICOLOR_FORCE` (other than `0`) turns them on, and else they are
+    /// used only when stdout is a terminal.
+    pub fn enabled(self) -> bool {
+        self.enabled_with(|name| env::var_os(name), io::stdout().is_terminal())
+    }
+
+    /// [`ColorChoice::enabled`] with the environment lookup and whether stdout
+    /// is a terminal passed in.
+    pub fn enabled_with(self, var: impl Fn(&str) -> Option<OsString>, is_terminal: bool) -> bool {
+        let set = |name: &str| var(name).filter(|value| !value.is_empty());
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto if set("NO_COLOR").is_some() => false,
+            ColorChoice::Auto if set("CLICOLOR_FORCE").is_some_and(|value| value != "0") => true,
+            ColorChoice::Auto => is_terminal,
+        }
+    }
+}
+
+const DEFAULT: &str = include_str!("../themes/default.toml");
+const HIGH_CONTRAST: &str = include_str!("../themes/high-contrast.toml");
+const MONOCHROME: &str = include_str!("../themes/monochrome.toml");
+const ASCII: &str = include_str!("../themes/ascii.toml");
+
+/// Optional parts of a task's display, in the order they appear. Ids and
+/// titles are always shown.
+pub const FIELDS: &[&str] =
+    &["status", "notes", "priority", "repeat", "created", "tags", "due", "completed", "depends_on", "checklist", "subtasks"];
+
+/// A text style such as `bold red` or `black on bright yellow`.
+///
+/// Written as space-separated words: a color (`red`, `bright blue`,
+/// `#ff8800`), `on` and a background color, and any of `bold`, `dimmed`,
+/// `italic`, `underline` and `reversed`. `plain` is no style at all.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Style {
+    spec: String,
+    fg: Option<Color>,
+    bg: Option<Color>,
+    bold: bool,
+    dimmed: bool,
+    italic: bool,
+    underline: bool,
+    reversed: bool,
+}
+
+impl Style {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut style = Style {
+            spec: spec.trim().to_string(),
+            fg: None,
+            bg: None,
+            bold: false,
+            dimmed: false,
+            italic: false,
+            underline: false,
+            reversed: false,
+        };
+        let lowercase = spec.to_lowercase();
+        let mut words = lowercase.split_whitespace();
+        let mut background = false;
+        while let Some(word) = words.next() {
+            match word {
+                "plain" => {}
+                "bold" => style.bold = true,
+                "dimmed" | "dim" => style.dimmed = true,
+                "italic" => style.italic = true,
+                "underline" => style.underline = true,
+                "reversed" => style.reversed = true,
+                "on" => background = true,
+                _ => {
+                    let color = match word {
+                        "bright" => words.next().and_then(|next| parse_color(&format!("bright {}", next))),
+                        word => parse_color(word),
+                    };
+                    let Some(color) = color else {
+                        return Err(Error::Invalid(format!(
+                            "Unknown style '{}' in '{}' (expected colors like red, bright blue or #ff8800, 'on <color>', bold, dimmed, italic, underline or reversed)",
+                            word, spec
+                        )));
+                    };
+                    if std::mem::take(&mut background) {
+                        style.bg = Some(color);
+                    } else {
+                        style.fg = Some(color);
+                    }
+                }
+            }
+        }
+        if background {
+            return Err(Error::Invalid(format!("Missing background color after 'on' in '{}'", spec)));
+        }
+        Ok(style)
+    }
+
+    pub fn paint(&self, text: &str) -> ColoredString {
+        let mut painted = text.normal();
+        if let Some(fg) = self.fg {
+            painted = painted.color(fg);
+        }
+        if let Some(bg) = self.bg {
+            painted = painted.on_color(bg);
+        }
+        if self.bold {
+            painted = painted.bold();
+        }
+        if self.dimmed {
+            painted = painted.dimmed();
+        }
+        if self.italic {
+            painted = painted.italic();
+        }
+        if self.underline {
+            painted = painted.underline();
+        }
+        if self.reversed {
+            painted = painted.reversed();
+        }
+        painted
+    }
+}
+
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.spec)
+    }
+}
+
+impl TryFrom<String> for Style {
+    type Error = Error;
+
+    fn try_from(spec: String) -> Result<Self> {
+        Style::parse(&spec)
+    }
+}
+
+impl From<Style> for String {
+    fn from(style: Style) -> String {
+        style.spec
+    }
+}
+
+fn parse_color(word: &str) -> Option<Color> {
+    if let Some(hex) = word.strip_prefix('#').filter(|hex| hex.len() == 6) {
+        let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+        return Some(Color::TrueColor { r: channel(0)?, g: channel(2)?, b: channel(4)? });
+    }
+    word.parse().ok()
+}
+
+/// The style of each kind of text.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Colors {
+    /// Success messages.
+    pub ok: Style,
+    pub warn: Style,
+    pub error: Style,
+    /// Ids, and titles and values in messages.
+    pub accent: Style,
+    /// Task titles in listings.
+    pub title: Style,
+    /// Secondary text such as dates.
+    pub muted: Style,
+    /// Labels of detail lines and section titles.
+    pub label: Style,
+    /// Group and section headings.
+    pub heading: Style,
+    pub high: Style,
+    pub medium: Style,
+    pub low: Style,
+    pub todo: Style,
+    pub in_progress: Style,
+    pub blocked: Style,
+    pub done: Style,
+    pub cancelled: Style,
+    /// The due date label of a task that is not overdue.
+    pub due: Style,
+    pub due_today: Style,
+    pub overdue: Style,
+    pub repeat: Style,
+    pub notes: Style,
+    /// Search matches.
+    pub highlight: Style,
+    pub banner: Style,
+}
+
+/// Every symbol the output is drawn with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Symbols {
+    pub ok: String,
+    pub error: String,
+    pub todo: String,
+    pub in_progress: String,
+    pub blocked: String,
+    pub done: String,
+    pub cancelled: String,
+    pub high: String,
+    pub medium: String,
+    pub low: String,
+    pub notes: String,
+    pub repeat: String,
+    pub detail: String,
+    pub group: String,
+    pub undo: String,
+    pub redo: String,
+    pub checked: String,
+    pub unchecked: String,
+    pub bullet: String,
+    pub quote: String,
+    pub tasks: String,
+    pub search: String,
+    pub due: String,
+    pub history: String,
+    pub backups: String,
+    pub banner: String,
+}
+
+impl Symbols {
+    /// Plain ASCII for terminals and logs that cannot show Unicode.
+    pub fn ascii() -> Self {
+        let mut config: toml::Table = ASCII.parse().unwrap();
+        config.remove("symbols").unwrap().try_into().unwrap()
+    }
+}
+
+/// Colors, symbols and the [`FIELDS`] shown for each task.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Theme {
+    pub colors: Colors,
+    pub symbols: Symbols,
+    pub fields: Vec<String>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::bundled("default").unwrap()
+    }
+}
+
+impl Theme {
+    /// One of the [`BUNDLED`] themes.
+    pub fn bundled(name: &str) -> Option<Self> {
+        let mut theme: toml::Table = DEFAULT.parse().unwrap();
+        let changes = match name {
+            "default" => "",
+            "high-contrast" => HIGH_CONTRAST,
+            "monochrome" => MONOCHROME,
+            _ => return None,
+        };
+        overlay(&mut theme, changes.parse().unwrap());
+        Some(toml::Value::Table(theme).try_into().unwrap())
+    }
+
+    /// Builds the theme from the text of a config file; one without a `[theme]`
+    /// table gives the default theme. Other settings are left to
+    /// [`Config`](crate::config::Config).
+    pub fn from_config(content: &str) -> Result<Self> {
+        let invalid = |err: &dyn fmt::Display| Error::Invalid(err.to_string().trim_end().to_string());
+        let mut config: toml::Table = content.parse().map_err(|err| invalid(&err))?;
+        let mut overrides = match config.remove("theme") {
+            Some(toml::Value::Table(table)) => table,
+            Some(_) => return Err(Error::Invalid("`theme` must be a table".to_string())),
+            None => return Ok(Theme::default()),
+        };
+
+        let base = match overrides.remove("base") {
+            Some(toml::Value::String(name)) => Theme::bundled(&name).ok_or_else(|| {
+                Error::Invalid(format!("Unknown theme '{}' (expected {})", name, BUNDLED.join(", ")))
+            })?,
+            Some(_) => return Err(Error::Invalid("`theme.base` must be a string".to_string())),
+            None => Theme::default(),
+        };
+
+        // Unknown names and bad styles in the overrides are reported by serde.
+        let mut theme = toml::Table::try_from(&base).map_err(|err| invalid(&err))?;
+        overlay(&mut theme, overrides);
+        let theme: Theme = toml::Value::Table(theme).try_into().map_err(|err| invalid(&err))?;
+
+        if let Some(field) = theme.fields.iter().find(|field| !FIELDS.contains(&field.as_str())) {
+            return Err(Error::Invalid(format!("Unknown field '{}' in theme.fields (expected {})", field, FIELDS.join(", "))));
+        }
+        Ok(theme)
+    }
+
+    /// Whether `field`, one of [`FIELDS`], is shown.
+    pub fn shows(&self, field: &str) -> bool {
+        self.fields.iter().any(|shown| shown == field)
+    }
+}
+
+/// Lays `changes` over `theme` one table entry at a time, so a theme can change
+/// a single color without repeating the others.
+fn overlay(theme: &mut toml::Table, changes: toml::Table) {
+    for (key, value) in changes {
+        match (theme.get_mut(&key), value) {
+            (Some(toml::Value::Table(table)), toml::Value::Table(entries)) => table.extend(entries),
+            (_, value) => {
+                theme.insert(key, value);
+            }
+        }
+    }
+}
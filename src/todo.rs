@@ -0,0 +1,278 @@
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::dates::{self, parse_due_date, DueDate};
+use crate::error::Result;
+use crate::recur::Recurrence;
+use crate::tree::Progress;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum Priority {
+    High,
+    Medium,
+    Low,
+}
+
+impl Priority {
+    /// Parses the lowercase names used on the command line, falling back to `Low`.
+    pub fn parse(s: Option<&str>) -> Self {
+        s.and_then(Priority::from_name).unwrap_or(Priority::Low)
+    }
+
+    /// Parses `high`, `medium` or `low`, returning `None` for anything else.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "high" => Some(Priority::High),
+            "medium" => Some(Priority::Medium),
+            "low" => Some(Priority::Low),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::High => "high",
+            Priority::Medium => "medium",
+            Priority::Low => "low",
+        }
+    }
+}
+
+/// Where a todo is in its lifecycle. Only `Done` carries a `completed_at` time.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum Status {
+    Todo,
+    InProgress,
+    Blocked,
+    Done,
+    Cancelled,
+}
+
+impl Status {
+    /// Statuses of work that still needs doing, shown by `list` by default.
+    pub const OPEN: [Status; 3] = [Status::Todo, Status::InProgress, Status::Blocked];
+
+    /// Parses `todo`, `in-progress`, `blocked`, `done` or `cancelled`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "todo" => Some(Status::Todo),
+            "in-progress" => Some(Status::InProgress),
+            "blocked" => Some(Status::Blocked),
+            "done" => Some(Status::Done),
+            "cancelled" => Some(Status::Cancelled),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Status::Todo => "todo",
+            Status::InProgress => "in-progress",
+            Status::Blocked => "blocked",
+            Status::Done => "done",
+            Status::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        Status::OPEN.contains(self)
+    }
+}
+
+/// One line of a todo's inline checklist.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChecklistItem {
+    pub text: String,
+    #[serde(default)]
+    pub done: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Todo {
+    pub id: usize,
+    /// Globally unique identifier, stable across machines that share a list.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<Uuid>,
+    pub title: String,
+    pub status: Status,
+    pub created_at: DateTime<Local>,
+    pub completed_at: Option<DateTime<Local>>,
+    pub priority: Priority,
+    pub due_date: Option<DueDate>,
+    /// IANA time zone the due date was set in, when known; for display only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_zone: Option<String>,
+    pub categories: Vec<String>,
+    /// Completing a recurring todo spawns its next occurrence, which takes the rule over.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<Recurrence>,
+    /// The todo this one is a subtask of.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<usize>,
+    /// Ids of the todos that must be finished before this one can start.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<usize>,
+    /// Free-form Markdown; `todo note` appends timestamped paragraphs.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checklist: Vec<ChecklistItem>,
+}
+
+/// A set of changes to apply to an existing todo. Fields left as `None` (or
+/// empty) are not touched; `created_at` and completion state are never changed.
+#[derive(Debug, Clone, Default)]
+pub struct TodoEdit {
+    pub title: Option<String>,
+    pub priority: Option<Priority>,
+    /// `Some(None)` clears the due date.
+    pub due_date: Option<Option<DueDate>>,
+    pub add_categories: Vec<String>,
+    pub remove_categories: Vec<String>,
+    /// `Some(None)` stops the todo from recurring.
+    pub recurrence: Option<Option<Recurrence>>,
+    /// `Some(None)` makes the todo top-level again.
+    pub parent_id: Option<Option<usize>>,
+    /// Replaces the notes; an empty string clears them.
+    pub notes: Option<String>,
+}
+
+impl TodoEdit {
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.priority.is_none()
+            && self.due_date.is_none()
+            && self.add_categories.is_empty()
+            && self.remove_categories.is_empty()
+            && self.recurrence.is_none()
+            && self.parent_id.is_none()
+            && self.notes.is_none()
+    }
+}
+
+impl Todo {
+    /// Builds a new todo; fails if `due_date_str` is given but not understood by
+    /// [`parse_due_date`].
+    pub fn new(title: String, priority_str: Option<String>, due_date_str: Option<String>, categories: Vec<String>) -> Result<Self> {
+        let priority = Priority::parse(priority_str.as_deref());
+
+        let due_date = due_date_str.as_deref().map(parse_due_date).transpose()?;
+
+        Ok(Todo {
+            id: 0, // Will be set when adding to list
+            uuid: Some(Uuid::new_v4()),
+            title,
+            status: Status::Todo,
+            created_at: Local::now(),
+            completed_at: None,
+            priority,
+            due_zone: due_date.and_then(|_| dates::local_zone()),
+            due_date,
+            categories,
+            recurrence: None,
+            parent_id: None,
+            depends_on: Vec::new(),
+            notes: String::new(),
+            checklist: Vec::new(),
+        })
+    }
+
+    /// Whole days from `today` until the due date in the local time zone: negative when overdue,
+    /// `None` without a due date.
+    pub fn days_until_due(&self, today: NaiveDate) -> Option<i64> {
+        self.due_date.map(|due| (dates::local_date(due) - today).num_days())
+    }
+
+    /// An open todo whose due date is before `today`.
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        self.status.is_open() && self.days_until_due(today).is_some_and(|days| days < 0)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.status == Status::Done
+    }
+
+    /// Moves the todo to `status`, stamping `completed_at` when it becomes
+    /// `Done` and clearing it when it leaves `Done`.
+    pub fn set_status(&mut self, status: Status) {
+        if status == Status::Done {
+            if self.status != Status::Done {
+                self.completed_at = Some(Local::now());
+            }
+        } else {
+            self.completed_at = None;
+        }
+        self.status = status;
+    }
+
+    pub fn apply(&mut self, edit: TodoEdit) {
+        if let Some(title) = edit.title {
+            self.title = title;
+        }
+        if let Some(priority) = edit.priority {
+            self.priority = priority;
+        }
+        if let Some(due_date) = edit.due_date {
+            self.due_zone = due_date.and_then(|_| dates::local_zone());
+            self.due_date = due_date;
+        }
+        if let Some(recurrence) = edit.recurrence {
+            self.recurrence = recurrence;
+        }
+        if let Some(parent_id) = edit.parent_id {
+            self.parent_id = parent_id;
+        }
+        if let Some(notes) = edit.notes {
+            self.notes = notes;
+        }
+        self.categories.retain(|c| !edit.remove_categories.contains(c));
+        for category in edit.add_categories {
+            if !self.categories.contains(&category) {
+                self.categories.push(category);
+            }
+        }
+    }
+
+    /// Appends `text` to the notes as a new block headed by the time `at` in bold.
+    pub fn append_note(&mut self, text: &str, at: DateTime<Local>) {
+        if !self.notes.is_empty() {
+            self.notes.push_str("\n\n");
+        }
+        self.notes.push_str(&format!("**{}**\n{}", at.format("%Y-%m-%d %H:%M"), text.trim()));
+    }
+
+    /// How many checklist items are ticked off, or `None` without a checklist.
+    pub fn checklist_progress(&self) -> Option<Progress> {
+        if self.checklist.is_empty() {
+            return None;
+        }
+        let done = self.checklist.iter().filter(|item| item.done).count();
+        Some(Progress { done, total: self.checklist.len() })
+    }
+
+    /// The next occurrence of a recurring todo completed at `completed_at`: a
+    /// fresh open copy with the following due date. `None` if it does not recur.
+    /// The checklist starts over unticked; notes stay with the finished
+    /// occurrence, since they record what happened that time.
+    pub fn next_occurrence(&self, completed_at: DateTime<Local>) -> Result<Option<Todo>> {
+        let Some(recurrence) = self.recurrence.as_ref() else {
+            return Ok(None);
+        };
+        let due_date = recurrence.next_due(self.due_date, completed_at)?;
+        let base = self.due_date.map_or(completed_at.date_naive(), dates::local_date);
+        Ok(Some(Todo {
+            id: 0,
+            uuid: Some(Uuid::new_v4()),
+            status: Status::Todo,
+            created_at: Local::now(),
+            completed_at: None,
+            due_date: Some(due_date),
+            due_zone: self.due_zone.clone(),
+            recurrence: Some(recurrence.anchored(base)),
+            notes: String::new(),
+            checklist: self.checklist.iter().map(|item| ChecklistItem { done: false, ..item.clone() }).collect(),
+            ..self.clone()
+        }))
+    }
+}
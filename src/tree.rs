@@ -0,0 +1,155 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::error::{Error, Result};
+use crate::todo::{Status, Todo};
+
+/// What closing or deleting a parent does to its subtasks.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ChildPolicy {
+    /// Apply the same change to every subtask, at any depth.
+    Cascade,
+    /// Fail with [`Error::HasSubtasks`] instead of leaving subtasks behind.
+    #[default]
+    Refuse,
+    /// Detach the affected subtasks so they become top-level tasks.
+    Orphan,
+}
+
+impl ChildPolicy {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "cascade" => Ok(ChildPolicy::Cascade),
+            "refuse" => Ok(ChildPolicy::Refuse),
+            "orphan" => Ok(ChildPolicy::Orphan),
+            _ => Err(Error::Invalid(format!("Unknown subtask rule '{}' (expected cascade, refuse or orphan)", name))),
+        }
+    }
+}
+
+/// How many of a parent's direct subtasks are done. Cancelled subtasks do not count.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    pub done: usize,
+    pub total: usize,
+}
+
+impl Progress {
+    pub fn of(todos: &[Todo], id: usize) -> Option<Progress> {
+        let children: Vec<&Todo> = todos
+            .iter()
+            .filter(|todo| todo.parent_id == Some(id) && todo.status != Status::Cancelled)
+            .collect();
+        if children.is_empty() {
+            return None;
+        }
+        Some(Progress { done: children.iter().filter(|todo| todo.is_done()).count(), total: children.len() })
+    }
+}
+
+/// Subtask progress and task statuses for a whole list, gathered in one pass so
+/// that rendering many rows does not rescan the list for each of them.
+#[derive(Debug, Clone, Default)]
+pub struct Overview {
+    progress: HashMap<usize, Progress>,
+    statuses: HashMap<usize, Status>,
+}
+
+impl Overview {
+    pub fn new(todos: &[Todo]) -> Self {
+        let mut overview = Overview::default();
+        for todo in todos {
+            overview.statuses.insert(todo.id, todo.status);
+            if let Some(parent) = todo.parent_id.filter(|_| todo.status != Status::Cancelled) {
+                let progress = overview.progress.entry(parent).or_insert(Progress { done: 0, total: 0 });
+                progress.total += 1;
+                progress.done += usize::from(todo.is_done());
+            }
+        }
+        overview
+    }
+
+    /// Same as [`Progress::of`].
+    pub fn progress(&self, id: usize) -> Option<Progress> {
+        self.progress.get(&id).copied()
+    }
+
+    /// Direct prerequisites of `todo` that are still open.
+    pub fn open_prerequisites(&self, todo: &Todo) -> Vec<usize> {
+        todo.depends_on
+            .iter()
+            .copied()
+            .filter(|id| self.statuses.get(id).is_some_and(|status| status.is_open()))
+            .collect()
+    }
+
+    /// An unblocked todo or one in progress whose prerequisites are all finished.
+    pub fn is_ready(&self, todo: &Todo) -> bool {
+        matches!(todo.status, Status::Todo | Status::InProgress) && self.open_prerequisites(todo).is_empty()
+    }
+}
+
+/// A todo placed in a tree, `depth` levels below its top-level ancestor.
+#[derive(Debug, Clone, Copy)]
+pub struct Node<'a> {
+    pub todo: &'a Todo,
+    pub depth: usize,
+}
+
+/// Arranges `todos` depth-first under their parents, keeping the given order
+/// among siblings. A todo whose parent is not in `todos` starts a tree of its own.
+pub fn tree<'a>(todos: &[&'a Todo]) -> Vec<Node<'a>> {
+    let present: HashSet<usize> = todos.iter().map(|todo| todo.id).collect();
+    let mut children: HashMap<usize, Vec<&'a Todo>> = HashMap::new();
+    for todo in todos {
+        if let Some(parent) = todo.parent_id.filter(|parent| present.contains(parent)) {
+            children.entry(parent).or_default().push(todo);
+        }
+    }
+    let mut seen = HashSet::new();
+    let mut nodes = Vec::with_capacity(todos.len());
+
+    let roots = todos.iter().filter(|todo| todo.parent_id.is_none_or(|parent| !present.contains(&parent)));
+    for root in roots {
+        push_subtree(&children, root, 0, &mut seen, &mut nodes);
+    }
+    // Members of a parent cycle in a hand-edited file have no root; show them flat.
+    for todo in todos {
+        if seen.insert(todo.id) {
+            nodes.push(Node { todo, depth: 0 });
+        }
+    }
+    nodes
+}
+
+fn push_subtree<'a>(
+    children: &HashMap<usize, Vec<&'a Todo>>,
+    todo: &'a Todo,
+    depth: usize,
+    seen: &mut HashSet<usize>,
+    nodes: &mut Vec<Node<'a>>,
+) {
+    if !seen.insert(todo.id) {
+        return;
+    }
+    nodes.push(Node { todo, depth });
+    for child in children.get(&todo.id).into_iter().flatten() {
+        push_subtree(children, child, depth + 1, seen, nodes);
+    }
+}
+
+/// Ids of every subtask below `id`, at any depth, in breadth-first order.
+pub(crate) fn descendants(todos: &[Todo], id: usize) -> Vec<usize> {
+    let mut found = vec![id];
+    let mut next = 0;
+    while next < found.len() {
+        let parent = found[next];
+        for todo in todos.iter().filter(|todo| todo.parent_id == Some(parent)) {
+            if !found.contains(&todo.id) {
+                found.push(todo.id);
+            }
+        }
+        next += 1;
+    }
+    found.remove(0);
+    found
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use std::fs;
+
+use common::TempDir;
+use todo_list::{Config, Error, TodoList, DEFAULT_BACKUP_LIMIT};
+
+fn titles(list: &TodoList) -> Vec<&str> {
+    list.todos().iter().map(|todo| todo.title.as_str()).collect()
+}
+
+#[test]
+fn keeps_only_the_newest_backups() {
+    let dir = TempDir::new("backup");
+    let mut list = TodoList::open_with_backup_limit(dir.data_file(), 2).unwrap();
+    for title in ["a", "b", "c", "d"] {
+        list.add(title.into(), None, None, vec![]).unwrap();
+    }
+    // The first save had nothing to back up; the next three did, and one was pruned.
+    let backups = list.backups().unwrap();
+    assert_eq!(backups.len(), 2);
+    assert!(backups[0].created_at >= backups[1].created_at);
+    assert!(fs::read_to_string(&backups[0].path).unwrap().contains("\"c\""));
+
+    list.set_backup_limit(0);
+    list.add("e".into(), None, None, vec![]).unwrap();
+    assert_eq!(list.backups().unwrap().len(), 2);
+}
+
+#[test]
+fn restores_a_backup_after_backing_up_the_current_file() {
+    let dir = TempDir::new("backup");
+    let path = dir.data_file();
+    let mut list = TodoList::open(&path).unwrap();
+    for title in ["a", "b", "c"] {
+        list.add(title.into(), None, None, vec![]).unwrap();
+    }
+    fs::write(&path, "not json").unwrap();
+
+    // Backups are newest first: 0 holds [a, b], 1 holds [a].
+    let (list, _) = TodoList::restore(&path, 1, DEFAULT_BACKUP_LIMIT).unwrap();
+    assert_eq!(titles(&list), ["a"]);
+    assert_eq!(titles(&TodoList::open(&path).unwrap()), ["a"]);
+    let backups = TodoList::backups_of(&path).unwrap();
+    assert_eq!(fs::read_to_string(&backups[0].path).unwrap(), "not json");
+
+    assert!(matches!(TodoList::restore(&path, 10, DEFAULT_BACKUP_LIMIT), Err(Error::BackupNotFound(10))));
+}
+
+#[test]
+fn reads_the_backup_limit_from_the_config() {
+    assert_eq!(Config::parse("").unwrap().backup_limit, DEFAULT_BACKUP_LIMIT);
+    assert_eq!(Config::parse("backup_limit = 0").unwrap().backup_limit, 0);
+    for config in ["backup_limit = -1", "backup_limit = \"ten\"", "backup_limt = 3"] {
+        assert!(matches!(Config::parse(config), Err(Error::Invalid(_))), "{}", config);
+    }
+
+    let dir = TempDir::new("backup");
+    let path = dir.path().join("config.toml");
+    fs::write(&path, "backup_limit = 12\n\n[theme]\nbase = \"monochrome\"\n").unwrap();
+    let config = Config::load(&path).unwrap();
+    assert_eq!(config.backup_limit, 12);
+    assert_eq!(config.theme, todo_list::Theme::bundled("monochrome").unwrap());
+    assert_eq!(Config::load(&dir.path().join("missing.toml")).unwrap(), Config::default());
+}
@@ -0,0 +1,58 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use todo_list::{format_due_date, local_due, parse_due_date_from, Error};
+
+// A Wednesday.
+fn today() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 11, 27).unwrap()
+}
+
+fn due(input: &str) -> NaiveDateTime {
+    parse_due_date_from(input, today()).unwrap_or_else(|err| panic!("{}: {}", input, err))
+}
+
+fn end_of(y: i32, m: u32, d: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(23, 59, 59).unwrap()
+}
+
+#[test]
+fn parses_explicit_dates_and_times() {
+    assert_eq!(due("2024-12-25"), end_of(2024, 12, 25));
+    assert_eq!(due("2024-12-25 14:00"), NaiveDate::from_ymd_opt(2024, 12, 25).unwrap().and_hms_opt(14, 0, 0).unwrap());
+    assert_eq!(due("2024-12-25T14:00"), due("2024-12-25 14:00"));
+}
+
+#[test]
+fn parses_relative_dates() {
+    assert_eq!(due("today"), end_of(2024, 11, 27));
+    assert_eq!(due("Tomorrow"), end_of(2024, 11, 28));
+    assert_eq!(due("friday"), end_of(2024, 11, 29));
+    assert_eq!(due("wed"), end_of(2024, 12, 4));
+    assert_eq!(due("next monday"), end_of(2024, 12, 2));
+    assert_eq!(due("next week"), end_of(2024, 12, 4));
+    assert_eq!(due("in 3 days"), end_of(2024, 11, 30));
+    assert_eq!(due("in 1 month"), end_of(2024, 12, 27));
+    assert_eq!(due("eow"), end_of(2024, 12, 1));
+    assert_eq!(due("eom"), end_of(2024, 11, 30));
+    assert_eq!(due("tomorrow 09:30"), NaiveDate::from_ymd_opt(2024, 11, 28).unwrap().and_hms_opt(9, 30, 0).unwrap());
+}
+
+#[test]
+fn rejects_unparseable_input() {
+    for input in ["", "2024-13-01", "2024-02-30", "someday", "in x days", "in 3 fortnights", "14:00", "in 4000000000 days", "in 4000000000 weeks", "in 4000000000 months"] {
+        assert!(matches!(parse_due_date_from(input, today()), Err(Error::Invalid(_))), "{:?}", input);
+    }
+}
+
+#[test]
+fn rejects_dates_past_the_calendar() {
+    assert!(matches!(parse_due_date_from("eom", NaiveDate::MAX), Err(Error::Invalid(_))));
+    for input in ["tomorrow", "next week", "eow", "friday", "in 1 day", "next month"] {
+        assert!(matches!(parse_due_date_from(input, NaiveDate::MAX), Err(Error::Invalid(_))), "{}", input);
+    }
+}
+
+#[test]
+fn formats_time_only_when_set() {
+    assert_eq!(format_due_date(local_due(due("2024-12-25"))), "2024-12-25");
+    assert_eq!(format_due_date(local_due(due("2024-12-25 14:00"))), "2024-12-25 14:00");
+}
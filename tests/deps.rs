@@ -0,0 +1,70 @@
+mod common;
+
+use common::TempDir;
+use todo_list::{Error, TodoList};
+
+/// A list of four tasks with no dependencies yet.
+fn list() -> (TempDir, TodoList) {
+    let dir = TempDir::new("deps");
+    let mut list = TodoList::open(dir.data_file()).unwrap();
+    for title in ["Design", "Build", "Test", "Ship"] {
+        list.add(title.into(), None, None, vec![]).unwrap();
+    }
+    (dir, list)
+}
+
+#[test]
+fn rejects_cycles_with_the_path() {
+    let (_dir, mut list) = list();
+    list.depend(2, &[1]).unwrap();
+    list.depend(3, &[2]).unwrap();
+    assert!(matches!(list.depend(1, &[3]), Err(Error::DependencyCycle(cycle)) if cycle == [1, 3, 2, 1]));
+    assert!(matches!(list.depend(1, &[1]), Err(Error::DependencyCycle(cycle)) if cycle == [1, 1]));
+    let message = list.depend(1, &[3]).unwrap_err().to_string();
+    assert_eq!(message, "That dependency would create a cycle: #1 -> #3 -> #2 -> #1");
+    assert!(matches!(list.depend(1, &[9]), Err(Error::NotFound(9))));
+    assert!(list.get(1).unwrap().depends_on.is_empty());
+}
+
+#[test]
+fn tasks_become_ready_when_prerequisites_finish() {
+    let (_dir, mut list) = list();
+    list.depend(4, &[2, 3]).unwrap();
+    assert_eq!(list.open_prerequisites(4), [2, 3]);
+    assert!(!list.is_ready(list.get(4).unwrap()));
+
+    list.complete(2).unwrap();
+    list.cancel(3).unwrap();
+    assert!(list.open_prerequisites(4).is_empty());
+    assert!(list.is_ready(list.get(4).unwrap()));
+
+    list.block(4).unwrap();
+    assert!(!list.is_ready(list.get(4).unwrap()));
+}
+
+#[test]
+fn overview_agrees_with_per_task_lookups() {
+    let (_dir, mut list) = list();
+    list.depend(4, &[2, 3]).unwrap();
+    list.depend(3, &[1]).unwrap();
+    list.complete(1).unwrap();
+    list.cancel(2).unwrap();
+    list.delete(1).unwrap();
+
+    let overview = list.overview();
+    for todo in list.todos() {
+        assert_eq!(overview.open_prerequisites(todo), list.open_prerequisites(todo.id), "#{}", todo.id);
+        assert_eq!(overview.is_ready(todo), list.is_ready(todo), "#{}", todo.id);
+    }
+    assert_eq!(overview.open_prerequisites(list.get(4).unwrap()), [3]);
+}
+
+#[test]
+fn deleting_a_prerequisite_drops_it() {
+    let (_dir, mut list) = list();
+    list.depend(4, &[1, 2]).unwrap();
+    list.delete(1).unwrap();
+    assert_eq!(list.get(4).unwrap().depends_on, [2]);
+    list.undepend(4, &[2]).unwrap();
+    assert!(list.get(4).unwrap().depends_on.is_empty());
+}
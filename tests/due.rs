@@ -0,0 +1,110 @@
+mod common;
+
+use std::process::Command;
+
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use common::TempDir;
+use todo_list::{local_due, relative_due, DueReport, Status, Todo, TodoList, OVERDUE_EXIT_CODE};
+
+// A Wednesday.
+fn today() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 11, 27).unwrap()
+}
+
+fn at(day: u32, hour: u32, minute: u32, second: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 11, day).unwrap().and_hms_opt(hour, minute, second).unwrap()
+}
+
+fn todo(id: usize, due: Option<NaiveDateTime>) -> Todo {
+    let mut todo = Todo::new(format!("task {}", id), None, None, vec![]).unwrap();
+    todo.id = id;
+    todo.due_date = due.map(local_due);
+    todo
+}
+
+fn ids(todos: &[&Todo]) -> Vec<usize> {
+    todos.iter().map(|todo| todo.id).collect()
+}
+
+#[test]
+fn labels_due_dates_relative_to_today() {
+    assert_eq!(relative_due(-3), "3 days overdue");
+    assert_eq!(relative_due(-1), "1 day overdue");
+    assert_eq!(relative_due(0), "due today");
+    assert_eq!(relative_due(1), "due tomorrow");
+    assert_eq!(relative_due(3), "due in 3 days");
+}
+
+#[test]
+fn buckets_by_day_not_by_time() {
+    let todos = [
+        todo(1, Some(at(26, 23, 59, 59))),
+        todo(2, Some(at(27, 0, 0, 0))),
+        todo(3, Some(at(27, 23, 59, 59))),
+        todo(4, Some(at(28, 0, 0, 0))),
+        todo(5, None),
+    ];
+    assert_eq!(todos[0].days_until_due(today()), Some(-1));
+    assert!(todos[0].is_overdue(today()));
+    assert!(!todos[1].is_overdue(today()));
+    assert_eq!(todos[2].days_until_due(today()), Some(0));
+    assert_eq!(todos[3].days_until_due(today()), Some(1));
+
+    let report = DueReport::new(&todos, today(), 7);
+    assert_eq!(ids(&report.overdue), [1]);
+    assert_eq!(ids(&report.today), [2, 3]);
+    assert_eq!(ids(&report.upcoming), [4]);
+    assert_eq!(ids(&report.todos()), [1, 2, 3, 4]);
+}
+
+#[test]
+fn window_includes_its_last_day_and_skips_closed_todos() {
+    let mut todos = vec![todo(1, Some(at(30, 9, 0, 0))), todo(2, Some(at(30, 23, 59, 59))), todo(3, Some(at(20, 12, 0, 0)))];
+    todos.push(todo(4, Some(at(20, 12, 0, 0))));
+    todos[3].set_status(Status::Done);
+    todos.push(todo(5, Some(at(20, 12, 0, 0))));
+    todos[4].set_status(Status::Cancelled);
+    assert!(!todos[3].is_overdue(today()));
+
+    let report = DueReport::new(&todos, today(), 3);
+    assert_eq!(ids(&report.overdue), [3]);
+    assert_eq!(ids(&report.upcoming), [1, 2]);
+    assert!(DueReport::new(&todos, today(), 2).upcoming.is_empty());
+}
+
+#[test]
+fn exit_code_is_set_only_when_something_is_overdue() {
+    let upcoming = [todo(1, Some(at(27, 0, 0, 0))), todo(2, Some(at(29, 12, 0, 0)))];
+    assert_eq!(DueReport::new(&upcoming, today(), 7).exit_code(), None);
+    assert_eq!(DueReport::new(&[], today(), 7).exit_code(), None);
+
+    let overdue = [todo(1, Some(at(26, 23, 59, 59)))];
+    assert_eq!(DueReport::new(&overdue, today(), 7).exit_code(), Some(OVERDUE_EXIT_CODE));
+}
+
+#[test]
+fn due_command_exits_with_the_overdue_status() {
+    let dir = TempDir::new("due");
+    let mut list = TodoList::open(dir.data_file()).unwrap();
+    list.add("Soon".into(), None, Some("tomorrow".into()), vec![]).unwrap();
+    list.save().unwrap();
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_todo_list"))
+            .arg("--file")
+            .arg(dir.data_file())
+            .args(args)
+            .env("TODO_CONFIG", dir.path().join("missing.toml"))
+            .output()
+            .unwrap()
+            .status
+            .code()
+    };
+    assert_eq!(run(&["due", "--exit-code"]), Some(0));
+
+    let yesterday = Local::now().date_naive().pred_opt().unwrap();
+    list.add("Late".into(), None, Some(yesterday.to_string()), vec![]).unwrap();
+    list.save().unwrap();
+    assert_eq!(run(&["due"]), Some(0));
+    assert_eq!(run(&["due", "--exit-code"]), Some(OVERDUE_EXIT_CODE));
+    assert_eq!(run(&["--format", "json", "due", "--exit-code"]), Some(OVERDUE_EXIT_CODE));
+}
@@ -0,0 +1,69 @@
+use serde_json::Value;
+use todo_list::export::Record;
+use todo_list::{Format, Todo};
+
+fn records() -> Vec<Record> {
+    let mut first = Todo::new("Buy milk, eggs".into(), Some("high".into()), Some("2024-05-01".into()), vec!["home".into()]).unwrap();
+    first.id = 1;
+    first.notes = "line one\nline\ttwo".into();
+    let mut second = Todo::new("Say \"hi\"".into(), None, None, vec![]).unwrap();
+    second.id = 2;
+    second.depends_on = vec![1];
+    vec![Record::from(&first), Record::scored(&second, 300)]
+}
+
+fn output(format: &str, records: &[Record]) -> String {
+    let mut out = Vec::new();
+    Format::parse(format).unwrap().write(&mut out, records).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn json_shape_is_stable() {
+    let records = records();
+    let json: Value = serde_json::from_str(&output("json", &records)).unwrap();
+    let first = json[0].as_object().unwrap();
+    let mut keys: Vec<&str> = first.keys().map(String::as_str).collect();
+    keys.sort_unstable();
+    assert_eq!(
+        keys,
+        [
+            "checklist", "completed", "created", "depends_on", "due", "due_zone", "id", "notes", "parent", "priority",
+            "recurrence", "status", "tags", "title", "uuid"
+        ]
+    );
+    assert_eq!(first["status"], "todo");
+    assert_eq!(first["priority"], "high");
+    assert!(first["due"].as_str().unwrap().starts_with("2024-05-01T23:59:59"));
+    assert_eq!(first["completed"], Value::Null);
+    assert_eq!(json[1]["score"], 300);
+    assert_eq!(json[1]["depends_on"], serde_json::json!([1]));
+
+    let lines: Vec<Value> = output("ndjson", &records).lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(lines, json.as_array().unwrap().clone());
+}
+
+#[test]
+fn csv_and_tsv_escape_values() {
+    let records = records();
+    let csv = output("csv", &records);
+    assert!(csv.starts_with("id,uuid,title,status,priority,tags,due,due_zone,created,completed,recurrence,parent,depends_on,notes,score\n"));
+    assert!(csv.contains(",\"Buy milk, eggs\",todo,high,home,"));
+    assert!(csv.contains(",\"line one\nline\ttwo\",\n"));
+    assert!(csv.contains(",\"Say \"\"hi\"\"\",todo,low,,"));
+
+    let tsv = output("tsv", &records);
+    assert_eq!(tsv.lines().count(), 3);
+    assert!(tsv.contains("\tline one\\nline\\ttwo\t\n"));
+    assert!(tsv.lines().all(|line| line.split('\t').count() == 15));
+}
+
+#[test]
+fn templates_fill_in_fields() {
+    let records = records();
+    assert_eq!(output(r"{id}\t{title}\t{score}", &records), "1\tBuy milk, eggs\t\n2\tSay \"hi\"\t300\n");
+    assert_eq!(output("{{{id}}} {depends_on}", &records), "{1} \n{2} 1\n");
+    assert!(Format::parse("{nope}").is_err());
+    assert!(Format::parse("yaml").is_err());
+    assert_eq!(Format::parse("text").unwrap(), Format::Text);
+}
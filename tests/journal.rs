@@ -0,0 +1,82 @@
+mod common;
+
+use std::fs;
+
+use common::TempDir;
+use todo_list::{Error, Status, TodoEdit, TodoList, DEFAULT_BACKUP_LIMIT};
+
+fn titles(list: &TodoList) -> Vec<&str> {
+    list.todos().iter().map(|todo| todo.title.as_str()).collect()
+}
+
+#[test]
+fn undoes_and_redoes_several_steps_across_runs() {
+    let dir = TempDir::new("journal");
+    let path = dir.data_file();
+    let mut list = TodoList::open(&path).unwrap();
+    list.add("a".into(), None, None, vec![]).unwrap();
+    list.add("b".into(), None, None, vec![]).unwrap();
+    list.complete(1).unwrap();
+    list.edit(2, TodoEdit { title: Some("B".into()), ..Default::default() }).unwrap();
+    list.delete(1).unwrap();
+    assert_eq!(list.history().operations.len(), 5);
+
+    let mut list = TodoList::open(&path).unwrap();
+    assert!(list.undo().unwrap().description.starts_with("delete"));
+    list.undo().unwrap();
+    list.undo().unwrap();
+    assert_eq!(titles(&list), ["a", "b"]);
+    assert_eq!(list.get(1).unwrap().status, Status::Todo);
+
+    let mut list = TodoList::open(&path).unwrap();
+    assert_eq!(titles(&list), ["a", "b"]);
+    list.redo().unwrap();
+    assert_eq!(list.get(1).unwrap().status, Status::Done);
+    list.redo().unwrap();
+    assert_eq!(titles(&list), ["a", "B"]);
+
+    // A new change drops what could still have been redone.
+    list.add("c".into(), None, None, vec![]).unwrap();
+    assert!(matches!(list.redo(), Err(Error::NothingToRedo)));
+    for _ in 0..5 {
+        list.undo().unwrap();
+    }
+    assert!(titles(&list).is_empty());
+    assert!(matches!(list.undo(), Err(Error::NothingToUndo)));
+}
+
+#[test]
+fn restoring_a_backup_clears_the_history() {
+    let dir = TempDir::new("journal");
+    let path = dir.data_file();
+    let mut list = TodoList::open(&path).unwrap();
+    list.add("a".into(), None, None, vec![]).unwrap();
+    list.add("b".into(), None, None, vec![]).unwrap();
+    list.undo().unwrap();
+
+    TodoList::restore(&path, 0, DEFAULT_BACKUP_LIMIT).unwrap();
+    let mut list = TodoList::open(&path).unwrap();
+    assert!(list.history().operations.is_empty());
+    assert!(matches!(list.undo(), Err(Error::NothingToUndo)));
+    assert!(matches!(list.redo(), Err(Error::NothingToRedo)));
+}
+
+#[test]
+fn recovering_a_corrupt_file_clears_the_history() {
+    let dir = TempDir::new("journal");
+    let path = dir.data_file();
+    let mut list = TodoList::open(&path).unwrap();
+    list.add("a".into(), None, None, vec![]).unwrap();
+    list.add("b".into(), None, None, vec![]).unwrap();
+    list.undo().unwrap();
+
+    fs::write(&path, r#"{ "todos": [ { "id": 1, "title": "a" } "#).unwrap();
+    let (list, _) = TodoList::recover(&path).unwrap();
+    list.save_recovered().unwrap();
+
+    // Redoing the add of #2 would bring back a task recovery never saw.
+    let mut list = TodoList::open(&path).unwrap();
+    assert!(matches!(list.redo(), Err(Error::NothingToRedo)));
+    assert!(matches!(list.undo(), Err(Error::NothingToUndo)));
+    assert_eq!(titles(&list), ["a"]);
+}
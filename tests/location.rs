@@ -0,0 +1,105 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+use common::TempDir;
+use todo_list::location::{find_project_file, FILE_ENV, PROJECT_FILE};
+
+/// A project with a `.todo.json` at its root and an empty `src/app` below it,
+/// next to an empty data directory.
+fn workspace() -> TempDir {
+    let dir = TempDir::new("location");
+    fs::create_dir_all(dir.path().join("project/src/app")).unwrap();
+    fs::create_dir_all(dir.path().join("data")).unwrap();
+    fs::write(dir.path().join("project").join(PROJECT_FILE), "[]").unwrap();
+    dir
+}
+
+/// Runs the binary in `cwd` with only the data directory and `TODO_FILE` set.
+fn todo(dir: &TempDir, cwd: &Path, env_file: Option<&Path>, args: &[&str]) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_todo_list"));
+    command
+        .args(args)
+        .current_dir(cwd)
+        .env("XDG_DATA_HOME", dir.path().join("data"))
+        .env("HOME", dir.path())
+        .env("TODO_CONFIG", dir.path().join("missing.toml"))
+        .env("NO_COLOR", "1")
+        .env_remove(FILE_ENV);
+    if let Some(file) = env_file {
+        command.env(FILE_ENV, file);
+    }
+    command.output().unwrap()
+}
+
+fn resolved(dir: &TempDir, cwd: &Path, env_file: Option<&Path>, args: &[&str]) -> String {
+    let output = todo(dir, cwd, env_file, &[args, &["path"]].concat());
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+#[test]
+fn finds_the_nearest_project_file() {
+    let dir = workspace();
+    let project = dir.path().join("project");
+    assert_eq!(find_project_file(&project.join("src/app")), Some(project.join(PROJECT_FILE)));
+    assert_eq!(find_project_file(&project), Some(project.join(PROJECT_FILE)));
+
+    // A nearer file wins over one further up; a directory of that name does not count.
+    fs::write(project.join("src").join(PROJECT_FILE), "[]").unwrap();
+    assert_eq!(find_project_file(&project.join("src/app")), Some(project.join("src").join(PROJECT_FILE)));
+    fs::create_dir(project.join("src/app").join(PROJECT_FILE)).unwrap();
+    assert_eq!(find_project_file(&project.join("src/app")), Some(project.join("src").join(PROJECT_FILE)));
+
+    assert_eq!(find_project_file(&dir.path().join("data")), None);
+}
+
+#[test]
+fn resolves_in_order_of_precedence() {
+    let dir = workspace();
+    let app = dir.path().join("project/src/app");
+    let explicit = dir.path().join("explicit.json");
+    let from_env = dir.path().join("env.json");
+    let project = dir.path().join("project").join(PROJECT_FILE);
+    let data = dir.path().join("data/todo/todos.json");
+
+    let flag = ["--file", explicit.to_str().unwrap()];
+    assert_eq!(resolved(&dir, &app, Some(&from_env), &flag), format!("{} (--file)", explicit.display()));
+    assert_eq!(resolved(&dir, &app, Some(&from_env), &[]), format!("{} ({})", from_env.display(), FILE_ENV));
+    // An empty TODO_FILE counts as unset.
+    assert_eq!(resolved(&dir, &app, Some(Path::new("")), &[]), format!("{} (project)", project.display()));
+    assert_eq!(resolved(&dir, &app, None, &[]), format!("{} (project)", project.display()));
+    assert_eq!(resolved(&dir, &dir.path().join("data"), None, &[]), format!("{} (data dir)", data.display()));
+}
+
+#[test]
+fn falls_back_to_a_legacy_file_until_the_data_dir_has_one() {
+    let dir = workspace();
+    let old = dir.path().join("old");
+    fs::create_dir(&old).unwrap();
+    fs::write(old.join("todos.json"), "[]").unwrap();
+
+    assert_eq!(resolved(&dir, &old, None, &[]), "todos.json (legacy ./todos.json)");
+    let output = todo(&dir, &old, None, &["list"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Using ./todos.json from an older version"), "{}", stdout);
+
+    // Machine-readable output keeps the notice out of stdout.
+    let output = todo(&dir, &old, None, &["--format", "json", "list"]);
+    assert!(!String::from_utf8(output.stdout).unwrap().contains("older version"));
+    assert!(String::from_utf8(output.stderr).unwrap().contains("older version"));
+
+    // A project file still comes first.
+    fs::write(old.join(PROJECT_FILE), "[]").unwrap();
+    assert_eq!(resolved(&dir, &old, None, &[]), format!("{} (project)", old.join(PROJECT_FILE).display()));
+    fs::remove_file(old.join(PROJECT_FILE)).unwrap();
+
+    // Once the data directory has a list, the old file is ignored.
+    let data = dir.path().join("data/todo/todos.json");
+    fs::create_dir_all(data.parent().unwrap()).unwrap();
+    fs::write(&data, "[]").unwrap();
+    assert_eq!(resolved(&dir, &old, None, &[]), format!("{} (data dir)", data.display()));
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use chrono::{Local, TimeZone};
+use common::TempDir;
+use todo_list::{ChecklistItem, Error, Progress, Todo, TodoList};
+
+fn list() -> (TempDir, TodoList) {
+    let dir = TempDir::new("notes");
+    let mut list = TodoList::open(dir.data_file()).unwrap();
+    list.add("Plan trip".into(), None, None, vec![]).unwrap();
+    (dir, list)
+}
+
+#[test]
+fn appends_timestamped_notes() {
+    let mut todo = Todo::new("Plan trip".into(), None, None, vec![]).unwrap();
+    todo.append_note("Budget is 2000", Local.with_ymd_and_hms(2024, 11, 27, 9, 30, 0).unwrap());
+    todo.append_note("  - book flights\n", Local.with_ymd_and_hms(2024, 11, 28, 18, 5, 0).unwrap());
+    assert_eq!(todo.notes, "**2024-11-27 09:30**\nBudget is 2000\n\n**2024-11-28 18:05**\n- book flights");
+}
+
+#[test]
+fn edits_the_checklist_as_undoable_operations() {
+    let (_dir, mut list) = list();
+    list.add_checklist_item(1, "Flights").unwrap();
+    list.add_checklist_item(1, "Insurance").unwrap();
+    list.set_checklist_item(1, 0, true).unwrap();
+    assert_eq!(list.get(1).unwrap().checklist_progress(), Some(Progress { done: 1, total: 2 }));
+
+    assert!(matches!(list.remove_checklist_item(1, 5), Err(Error::Invalid(_))));
+    list.remove_checklist_item(1, 0).unwrap();
+    assert_eq!(list.get(1).unwrap().checklist, [ChecklistItem { text: "Insurance".into(), done: false }]);
+
+    list.undo().unwrap();
+    assert_eq!(list.get(1).unwrap().checklist.len(), 2);
+    assert_eq!(TodoList::open(list.path()).unwrap().get(1).unwrap().checklist.len(), 2);
+}
+
+#[test]
+fn rejects_empty_notes_and_items() {
+    let (_dir, mut list) = list();
+    assert!(matches!(list.note(1, "  "), Err(Error::Invalid(_))));
+    assert!(matches!(list.add_checklist_item(1, ""), Err(Error::Invalid(_))));
+    assert_eq!(list.history().operations.len(), 1);
+}
@@ -0,0 +1,154 @@
+use chrono::{Local, NaiveDate, TimeZone};
+use todo_list::query::{Cmp, ListFilter, Term};
+use todo_list::{local_date, parse_ids, Error, Priority, Query, Status, Todo};
+
+fn todo(title: &str, priority: &str, due: Option<&str>, tags: &[&str]) -> Todo {
+    Todo::new(
+        title.to_string(),
+        Some(priority.to_string()),
+        due.map(String::from),
+        tags.iter().map(|tag| tag.to_string()).collect(),
+    )
+    .unwrap()
+}
+
+fn matches(query: &str, todo: &Todo) -> bool {
+    Query::parse(query).unwrap().matches(todo)
+}
+
+#[test]
+fn parses_terms() {
+    assert_eq!(Query::parse("priority>=medium").unwrap(), Query::Term(Term::Priority(Cmp::Ge, Priority::Medium)));
+    assert_eq!(Query::parse("done").unwrap(), Query::Term(Term::Status(vec![Status::Done])));
+    assert_eq!(Query::parse("\"Buy Milk\"").unwrap(), Query::Term(Term::Title("buy milk".to_string())));
+    assert_eq!(Query::parse("   ").unwrap(), Query::All);
+}
+
+#[test]
+fn evaluates_the_documented_example() {
+    let query = "priority:high and (tag:work or tag:urgent) and due<2024-12-31 and not done";
+    assert!(matches(query, &todo("Report", "high", Some("2024-12-20"), &["urgent"])));
+    assert!(!matches(query, &todo("Report", "high", Some("2025-01-02"), &["work"])));
+    assert!(!matches(query, &todo("Report", "medium", Some("2024-12-20"), &["work"])));
+    assert!(!matches(query, &todo("Report", "high", None, &["work"])));
+
+    let mut done = todo("Report", "high", Some("2024-12-20"), &["work"]);
+    done.set_status(Status::Done);
+    assert!(!matches(query, &done));
+}
+
+#[test]
+fn or_binds_looser_than_and() {
+    let milk = todo("Buy milk", "low", None, &["home"]);
+    assert!(matches("tag:work and priority:high or milk", &milk));
+    assert!(!matches("tag:work and (priority:high or milk)", &milk));
+    assert!(matches("tag:home priority:low", &milk));
+}
+
+#[test]
+fn compares_dates_and_presence() {
+    let todo = todo("Taxes", "low", Some("2025-04-15"), &[]);
+    assert!(matches("due>=2025-04-15 and due<=2025-04-15", &todo));
+    assert!(matches("due:any and completed:none", &todo));
+    assert!(matches(&format!("created:{}", Local::now().date_naive()), &todo));
+    assert_eq!(local_date(todo.due_date.unwrap()), NaiveDate::from_ymd_opt(2025, 4, 15).unwrap());
+}
+
+#[test]
+fn reports_error_positions() {
+    for (input, position) in [("tag:work and", 12), ("(tag:work", 0), ("tag:work priority:urgent", 9), ("title<x", 0)] {
+        match Query::parse(input) {
+            Err(Error::Query { position: found, .. }) => assert_eq!(found, position, "{}", input),
+            other => panic!("{}: expected a query error, got {:?}", input, other),
+        }
+    }
+}
+
+#[test]
+fn parses_id_lists_skipping_gaps_in_ranges() {
+    let in_use = [1, 2, 3, 4, 6, 7, 9];
+    assert_eq!(parse_ids("3, 1-4,2", &in_use).unwrap(), [3, 1, 2, 4]);
+    assert_eq!(parse_ids("2-7", &in_use).unwrap(), [2, 3, 4, 6, 7]);
+    assert_eq!(parse_ids("7-100000000000", &in_use).unwrap(), [7, 9]);
+    assert!(parse_ids("20-30", &in_use).unwrap().is_empty());
+    // Ids named one by one are kept so that a missing one is reported.
+    assert_eq!(parse_ids("5,9", &in_use).unwrap(), [5, 9]);
+    for spec in ["", " , ", "0", "4-2", "a-b", "1-"] {
+        assert!(matches!(parse_ids(spec, &in_use), Err(Error::Invalid(_))), "{:?}", spec);
+    }
+}
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+/// One todo in each status, with #4 completed on 2024-11-20 at 23:30.
+fn statuses() -> Vec<Todo> {
+    let mut todos = Vec::new();
+    for (id, status) in [Status::Todo, Status::InProgress, Status::Blocked, Status::Done, Status::Cancelled].into_iter().enumerate() {
+        let mut todo = todo(&format!("task {}", id + 1), "low", None, &[]);
+        todo.id = id + 1;
+        todo.set_status(status);
+        todos.push(todo);
+    }
+    todos[3].completed_at = Some(Local.with_ymd_and_hms(2024, 11, 20, 23, 30, 0).unwrap());
+    todos
+}
+
+fn shown(filter: ListFilter) -> Vec<usize> {
+    let query = filter.to_query();
+    statuses().iter().filter(|todo| query.matches(todo)).map(|todo| todo.id).collect()
+}
+
+#[test]
+fn list_flags_choose_statuses() {
+    assert_eq!(shown(ListFilter::default()), [1, 2, 3]);
+    assert_eq!(shown(ListFilter { open: true, ..Default::default() }), [1, 2, 3]);
+    assert_eq!(shown(ListFilter { all: true, ..Default::default() }), [1, 2, 3, 4, 5]);
+    assert_eq!(shown(ListFilter { done: true, ..Default::default() }), [4]);
+    let statuses = vec![Status::Blocked, Status::Cancelled];
+    assert_eq!(shown(ListFilter { statuses, done: true, ..Default::default() }), [3, 5]);
+
+    // A status in the expression replaces the open-only default.
+    let query = Some(Query::parse("cancelled or done").unwrap());
+    assert_eq!(shown(ListFilter { query, ..Default::default() }), [4, 5]);
+}
+
+#[test]
+fn completion_window_keeps_open_tasks() {
+    let since = |day| ListFilter { since: Some(date(2024, 11, day)), ..Default::default() };
+    let until = |day| ListFilter { until: Some(date(2024, 11, day)), ..Default::default() };
+    // Both ends are inclusive and compare the local completion day.
+    assert_eq!(shown(since(20)), [1, 2, 3, 4]);
+    assert_eq!(shown(since(21)), [1, 2, 3]);
+    assert_eq!(shown(until(20)), [1, 2, 3, 4]);
+    assert_eq!(shown(until(19)), [1, 2, 3]);
+    assert_eq!(shown(ListFilter { since: Some(date(2024, 11, 20)), until: Some(date(2024, 11, 20)), ..Default::default() }), [1, 2, 3, 4]);
+
+    // Cancelled tasks were never completed, so no window includes them.
+    assert_eq!(shown(ListFilter { all: true, ..since(1) }), [1, 2, 3, 4]);
+    assert_eq!(shown(ListFilter { statuses: vec![Status::Cancelled], ..since(1) }), Vec::<usize>::new());
+
+    // Explicit statuses still narrow the window.
+    assert_eq!(shown(ListFilter { done: true, ..since(20) }), [4]);
+    assert_eq!(shown(ListFilter { open: true, ..since(20) }), [1, 2, 3]);
+}
+
+#[test]
+fn list_flags_combine_with_the_expression() {
+    let mut todos = [todo("Report", "high", None, &["work"]), todo("Milk", "high", None, &["home"]), todo("Call", "low", None, &["work"])];
+    todos[2].set_status(Status::Done);
+    let filter = ListFilter {
+        query: Some(Query::parse("not milk").unwrap()),
+        all: true,
+        tag: Some("work".into()),
+        ..Default::default()
+    };
+    let query = filter.to_query();
+    let titles: Vec<&str> = todos.iter().filter(|todo| query.matches(todo)).map(|todo| todo.title.as_str()).collect();
+    assert_eq!(titles, ["Report", "Call"]);
+
+    let query = ListFilter { priority: Some(Priority::High), tag: Some("work".into()), ..Default::default() }.to_query();
+    let titles: Vec<&str> = todos.iter().filter(|todo| query.matches(todo)).map(|todo| todo.title.as_str()).collect();
+    assert_eq!(titles, ["Report"]);
+}
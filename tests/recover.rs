@@ -0,0 +1,92 @@
+mod common;
+
+use std::fs;
+
+use common::TempDir;
+use serde_json::{json, Value};
+use todo_list::{Error, TodoList};
+
+#[test]
+fn corrupt_files_are_quarantined_and_left_in_place() {
+    let dir = TempDir::new("recover");
+    let path = dir.data_file();
+    let damaged = r#"{ "schema_version": 4, "next_id": 3, "todos": [ { "id": 1, "title": "a" "#;
+    fs::write(&path, damaged).unwrap();
+
+    let quarantined = match TodoList::open(&path) {
+        Err(Error::Corrupt { quarantined: Some(quarantined), line: 1, .. }) => quarantined,
+        other => panic!("expected a quarantined corrupt file, got {:?}", other.err()),
+    };
+    assert_eq!(fs::read_to_string(&quarantined).unwrap(), damaged);
+    assert_eq!(fs::read_to_string(&path).unwrap(), damaged);
+    assert!(matches!(TodoList::open(&path), Err(Error::Corrupt { quarantined: Some(again), .. }) if again == quarantined));
+}
+
+#[test]
+fn salvages_intact_entries_from_invalid_json() {
+    let dir = TempDir::new("recover");
+    let path = dir.data_file();
+    fs::write(
+        &path,
+        r#"{ "schema_version": 4, "next_id": 4, "todos": [
+            { "id": 1, "title": "Keep me", "status": "Done", "priority": "High", "categories": ["home"] },
+            { "id": 2, "title": "Defaulted", "priority": 7 },
+            { "id": 3, "title": "Lost", "status": tru }"#,
+    )
+    .unwrap();
+
+    let (mut list, recovery) = TodoList::recover(&path).unwrap();
+    assert_eq!(recovery.recovered, 2);
+    assert_eq!(recovery.skipped, 1);
+    assert!(recovery.warnings.iter().any(|warning| warning.contains("id 3")));
+    assert!(recovery.warnings.iter().any(|warning| warning.contains("priority")));
+    let titles: Vec<&str> = list.todos().iter().map(|todo| todo.title.as_str()).collect();
+    assert_eq!(titles, ["Keep me", "Defaulted"]);
+    assert_eq!(list.get(1).unwrap().categories, ["home"]);
+
+    // #3 is gone, but its id must not be handed out again.
+    assert_eq!(list.add("New".into(), None, None, vec![]).unwrap().id, 4);
+}
+
+#[test]
+fn recovery_keeps_next_id_past_the_newest_backup() {
+    let dir = TempDir::new("recover");
+    let path = dir.data_file();
+    let mut list = TodoList::open(&path).unwrap();
+    for title in ["a", "b", "c"] {
+        list.add(title.into(), None, None, vec![]).unwrap();
+    }
+    list.delete(3).unwrap();
+    list.delete(2).unwrap();
+
+    // Truncated so that neither `next_id` nor the deleted ids survive.
+    fs::write(&path, r#"{ "todos": [ { "id": 1, "title": "a" } "#).unwrap();
+    let (mut list, _) = TodoList::recover(&path).unwrap();
+    assert_eq!(list.add("d".into(), None, None, vec![]).unwrap().id, 4);
+}
+
+#[test]
+fn duplicate_ids_get_fresh_ones() {
+    let dir = TempDir::new("recover");
+    let path = dir.data_file();
+    let mut list = TodoList::open(&path).unwrap();
+    for title in ["a", "b", "c", "d"] {
+        list.add(title.into(), None, None, vec![]).unwrap();
+    }
+    let mut root: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    root["next_id"] = json!(3);
+    root["todos"][2]["id"] = json!(1);
+    root["todos"][3]["id"] = json!(0);
+    root["todos"][3].as_object_mut().unwrap().remove("uuid");
+    fs::write(&path, root.to_string()).unwrap();
+
+    let list = TodoList::open(&path).unwrap();
+    let ids: Vec<usize> = list.todos().iter().map(|todo| todo.id).collect();
+    assert_eq!(ids, [1, 2, 3, 4]);
+    let repairs: Vec<(usize, usize)> = list.repairs().iter().map(|repair| (repair.old_id, repair.new_id)).collect();
+    assert_eq!(repairs, [(1, 3), (0, 4)]);
+    assert!(list.todos().iter().all(|todo| todo.uuid.is_some()));
+
+    // The repaired list was saved, so the next open has nothing to fix.
+    assert!(TodoList::open(&path).unwrap().repairs().is_empty());
+}
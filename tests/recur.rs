@@ -0,0 +1,110 @@
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Weekday};
+use todo_list::{local_date, local_due, ChecklistItem, Recurrence, Status, Todo};
+
+fn rule(input: &str) -> Recurrence {
+    Recurrence::parse(input).unwrap_or_else(|err| panic!("{}: {}", input, err))
+}
+
+fn at(y: i32, m: u32, d: u32) -> DateTime<Local> {
+    Local.with_ymd_and_hms(y, m, d, 12, 0, 0).unwrap()
+}
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+/// The local day of the occurrence after one due at the end of `due`.
+fn next(input: &str, due: NaiveDate) -> NaiveDate {
+    let due = local_due(due.and_hms_opt(23, 59, 59).unwrap());
+    local_date(rule(input).next_due(Some(due), at(2024, 11, 30)).unwrap())
+}
+
+#[test]
+fn parses_shorthands_and_rrules() {
+    assert_eq!(rule("daily"), Recurrence::Daily { interval: 1 });
+    assert_eq!(rule("every 3 days"), Recurrence::Daily { interval: 3 });
+    assert_eq!(rule("weekly on mon,thu"), Recurrence::Weekly { interval: 1, weekdays: vec![Weekday::Mon, Weekday::Thu] });
+    assert_eq!(rule("every friday"), Recurrence::Weekly { interval: 1, weekdays: vec![Weekday::Fri] });
+    assert_eq!(rule("every 2 weeks"), Recurrence::Weekly { interval: 2, weekdays: vec![] });
+    assert_eq!(rule("Monthly on the 15th"), Recurrence::Monthly { interval: 1, day: Some(15) });
+    assert_eq!(rule("every 10 days after done"), Recurrence::AfterCompletion { days: 10 });
+    assert_eq!(rule("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR"), Recurrence::Weekly { interval: 2, weekdays: vec![Weekday::Mon, Weekday::Fri] });
+
+    for input in ["hourly", "every 0 days", "monthly on 32", "weekly on funday", "FREQ=YEARLY", "every 2 weeks after done"] {
+        assert!(Recurrence::parse(input).is_err(), "{:?}", input);
+    }
+}
+
+#[test]
+fn round_trips_through_rrule_and_description() {
+    for input in ["daily", "every 3 days", "weekly on mon,thu", "every 2 weeks", "monthly on 31", "every 4 months", "every 10 days after done"] {
+        let recurrence = rule(input);
+        assert_eq!(rule(&recurrence.to_string()), recurrence, "{}", recurrence);
+        assert_eq!(rule(&recurrence.describe()), recurrence, "{}", recurrence.describe());
+    }
+}
+
+#[test]
+fn computes_the_next_due_date() {
+    let wednesday = date(2024, 11, 27);
+    assert_eq!(next("daily", wednesday), date(2024, 11, 28));
+    assert_eq!(next("weekly", wednesday), date(2024, 12, 4));
+    assert_eq!(next("weekly on mon,thu", wednesday), date(2024, 11, 28));
+    assert_eq!(next("every 2 weeks on mon", wednesday), date(2024, 12, 9));
+    assert_eq!(next("monthly", date(2024, 1, 31)), date(2024, 2, 29));
+    assert_eq!(next("monthly on 31", date(2024, 2, 29)), date(2024, 3, 31));
+    assert_eq!(next("every 10 days after done", wednesday), date(2024, 12, 10));
+}
+
+#[test]
+fn huge_intervals_fail_instead_of_panicking() {
+    let due = local_due(date(2024, 11, 27).and_hms_opt(23, 59, 59).unwrap());
+    for input in [
+        "every 4294967295 days",
+        "FREQ=WEEKLY;INTERVAL=4294967295",
+        "every 4294967295 months",
+        "every 4294967295 days after done",
+        // Lands in the last representable month, which has no month after it.
+        "FREQ=MONTHLY;INTERVAL=3121417",
+    ] {
+        assert!(rule(input).next_due(Some(due), at(2024, 11, 30)).is_err(), "{}", input);
+    }
+
+    let mut todo = Todo::new("Someday".into(), None, Some("2024-11-27".into()), vec![]).unwrap();
+    todo.recurrence = Some(rule("every 4294967295 months"));
+    todo.set_status(Status::Done);
+    assert!(todo.next_occurrence(at(2024, 11, 30)).is_err());
+}
+
+#[test]
+fn next_occurrence_takes_over_the_rule() {
+    let mut todo = Todo::new("Water plants".into(), None, Some("2024-01-31".into()), vec!["home".into()]).unwrap();
+    todo.recurrence = Some(rule("monthly"));
+    todo.set_status(Status::Done);
+
+    let next = todo.next_occurrence(at(2024, 2, 1)).unwrap().unwrap();
+    assert_eq!(next.status, Status::Todo);
+    assert_eq!(next.categories, todo.categories);
+    assert_ne!(next.uuid, todo.uuid);
+    assert_eq!(local_date(next.due_date.unwrap()), date(2024, 2, 29));
+    assert_eq!(next.recurrence, Some(Recurrence::Monthly { interval: 1, day: Some(31) }));
+}
+
+#[test]
+fn next_occurrence_starts_a_fresh_checklist_without_notes() {
+    let mut todo = Todo::new("Pack for the trip".into(), None, Some("2024-03-01".into()), vec![]).unwrap();
+    todo.recurrence = Some(rule("monthly"));
+    todo.checklist = vec![
+        ChecklistItem { text: "Passport".into(), done: true },
+        ChecklistItem { text: "Charger".into(), done: false },
+    ];
+    todo.append_note("Forgot the charger", at(2024, 3, 1));
+    todo.set_status(Status::Done);
+
+    let next = todo.next_occurrence(at(2024, 3, 1)).unwrap().unwrap();
+    assert_eq!(next.checklist.iter().map(|item| item.text.as_str()).collect::<Vec<_>>(), ["Passport", "Charger"]);
+    assert!(next.checklist.iter().all(|item| !item.done));
+    assert_eq!(next.checklist_progress().unwrap().done, 0);
+    assert!(next.notes.is_empty());
+    assert_eq!(todo.notes.lines().last(), Some("Forgot the charger"));
+}
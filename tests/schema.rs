@@ -0,0 +1,116 @@
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDate};
+use serde_json::{json, Value};
+use common::TempDir;
+use todo_list::schema::{self, CURRENT_VERSION};
+use todo_list::{Error, TodoList};
+
+fn todo(id: usize, title: &str) -> Value {
+    json!({
+        "id": id,
+        "title": title,
+        "completed": false,
+        "created_at": "2024-11-26T03:02:53.240376900-05:00",
+        "completed_at": null,
+        "priority": "High",
+        "due_date": "2024-12-25T23:59:59",
+        "categories": ["work"]
+    })
+}
+
+fn temp_file(content: &Value) -> (TempDir, PathBuf) {
+    let dir = TempDir::new("schema");
+    let path = dir.data_file();
+    fs::write(&path, serde_json::to_string_pretty(content).unwrap()).unwrap();
+    (dir, path)
+}
+
+#[test]
+fn detects_every_version() {
+    assert_eq!(schema::detect_version(&json!([])), 1);
+    assert_eq!(schema::detect_version(&json!({ "next_id": 1, "todos": [] })), 2);
+    assert_eq!(schema::detect_version(&json!({ "schema_version": 3, "next_id": 1, "todos": [] })), 3);
+    assert_eq!(schema::detect_version(&json!("nope")), 0);
+}
+
+#[test]
+fn v1_to_v2_wraps_bare_array() {
+    let migrated = schema::migrate_once(json!([todo(1, "a")])).unwrap();
+    assert_eq!(migrated, json!({ "next_id": 0, "todos": [todo(1, "a")] }));
+    assert_eq!(schema::detect_version(&migrated), 2);
+}
+
+#[test]
+fn v2_to_v3_adds_version_marker() {
+    let migrated = schema::migrate_once(json!({ "next_id": 4, "todos": [todo(3, "a")] })).unwrap();
+    assert_eq!(migrated, json!({ "schema_version": 3, "next_id": 4, "todos": [todo(3, "a")] }));
+}
+
+#[test]
+fn current_version_is_left_alone() {
+    let root = json!({ "schema_version": CURRENT_VERSION, "next_id": 2, "todos": [todo(1, "a")] });
+    assert_eq!(schema::migrate_once(root.clone()).unwrap(), root);
+}
+
+#[test]
+fn newer_versions_are_rejected() {
+    let root = json!({ "schema_version": CURRENT_VERSION + 1, "next_id": 1, "todos": [] });
+    assert!(matches!(schema::migrate(root), Err(Error::UnsupportedSchema(v)) if v == CURRENT_VERSION + 1));
+}
+
+#[test]
+fn opening_a_bare_array_upgrades_and_saves_it() {
+    let (_dir, path) = temp_file(&json!([todo(1, "a"), todo(2, "b")]));
+    let list = TodoList::open(&path).unwrap();
+    assert_eq!(list.migrated_from(), Some(1));
+    assert_eq!(list.todos().len(), 2);
+
+    let saved: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(saved["schema_version"], json!(CURRENT_VERSION));
+    assert_eq!(saved["next_id"], json!(3));
+    assert_eq!(TodoList::open(&path).unwrap().migrated_from(), None);
+}
+
+#[test]
+fn opening_a_future_file_leaves_it_untouched() {
+    let root = json!({ "schema_version": CURRENT_VERSION + 1, "next_id": 1, "todos": [] });
+    let (_dir, path) = temp_file(&root);
+    let before = fs::read_to_string(&path).unwrap();
+    assert!(matches!(TodoList::open(&path), Err(Error::UnsupportedSchema(_))));
+    assert_eq!(fs::read_to_string(&path).unwrap(), before);
+}
+
+#[test]
+fn v3_to_v4_replaces_completed_with_status() {
+    let mut done = todo(2, "b");
+    done["completed"] = json!(true);
+    let root = json!({ "schema_version": 3, "next_id": 3, "todos": [todo(1, "a"), done] });
+
+    let migrated = schema::migrate_once(root).unwrap();
+    assert_eq!(migrated["schema_version"], json!(4));
+    assert_eq!(migrated["todos"][0]["status"], json!("Todo"));
+    assert_eq!(migrated["todos"][1]["status"], json!("Done"));
+    assert!(migrated["todos"][1].get("completed").is_none());
+}
+
+#[test]
+fn v4_to_v5_pins_naive_due_dates_to_the_local_zone() {
+    let mut pinned = todo(2, "b");
+    pinned["due_date"] = json!("2024-12-25T09:00:00+09:00");
+    let mut undated = todo(3, "c");
+    undated["due_date"] = Value::Null;
+    let root = json!({ "schema_version": 4, "next_id": 4, "todos": [todo(1, "a"), pinned, undated] });
+
+    let migrated = schema::migrate_once(root).unwrap();
+    assert_eq!(migrated["schema_version"], json!(5));
+
+    let due: DateTime<FixedOffset> = migrated["todos"][0]["due_date"].as_str().unwrap().parse().unwrap();
+    let expected = NaiveDate::from_ymd_opt(2024, 12, 25).unwrap().and_hms_opt(23, 59, 59).unwrap();
+    assert_eq!(due.with_timezone(&Local).naive_local(), expected);
+    assert_eq!(migrated["todos"][1]["due_date"], json!("2024-12-25T09:00:00+09:00"));
+    assert_eq!(migrated["todos"][2]["due_date"], Value::Null);
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use common::TempDir;
+use todo_list::search::{Field, Hit};
+use todo_list::{Search, Todo, TodoEdit, TodoList};
+
+fn todo(id: usize, title: &str, tags: &[&str], notes: &str) -> Todo {
+    let mut todo = Todo::new(title.into(), None, None, tags.iter().map(|tag| tag.to_string()).collect()).unwrap();
+    todo.id = id;
+    todo.notes = notes.into();
+    todo
+}
+
+fn todos() -> Vec<Todo> {
+    vec![
+        todo(1, "Call plumber", &["projects"], ""),
+        todo(2, "Buy groceries", &["home"], "Ask about the project budget"),
+        todo(3, "Project management review", &["work"], ""),
+    ]
+}
+
+fn ids(hits: &[Hit]) -> Vec<usize> {
+    hits.iter().map(|hit| hit.todo.id).collect()
+}
+
+#[test]
+fn ranks_titles_over_tags_over_notes() {
+    let todos = todos();
+    assert_eq!(ids(&Search::fuzzy("project").run(&todos)), [3, 1, 2]);
+}
+
+#[test]
+fn tolerates_typos_in_longer_words() {
+    let todos = todos();
+    assert_eq!(ids(&Search::fuzzy("mangement").run(&todos)), [3]);
+    assert_eq!(ids(&Search::fuzzy("PLUMBR").run(&todos)), [1]);
+    assert_eq!(ids(&Search::fuzzy("buy plumber").run(&todos)), Vec::<usize>::new());
+    assert_eq!(ids(&Search::fuzzy("cal").run(&todos)), [1]);
+    assert!(Search::fuzzy("xyz").run(&todos).is_empty());
+    assert!(Search::fuzzy("  ").run(&todos).is_empty());
+}
+
+#[test]
+fn matches_words_sharing_a_stem() {
+    let todos = vec![todo(1, "Buy grocery bags", &[], ""), todo(2, "Plan running route", &[], "")];
+    assert_eq!(ids(&Search::fuzzy("groceries").run(&todos)), [1]);
+    assert_eq!(ids(&Search::fuzzy("runs").run(&todos)), [2]);
+}
+
+#[test]
+fn persisted_index_finds_what_a_scan_finds() {
+    let dir = TempDir::new("search");
+    let path = dir.data_file();
+    let mut list = TodoList::open(&path).unwrap();
+    for todo in todos() {
+        list.add_todo(todo).unwrap();
+    }
+    list.save().unwrap();
+    assert!(dir.path().join(".todos.json.index").exists());
+
+    let mut list = TodoList::open(&path).unwrap();
+    list.edit(1, TodoEdit { title: Some("Call the electrician".into()), ..Default::default() }).unwrap();
+    list.delete(2).unwrap();
+    list.save().unwrap();
+
+    let list = TodoList::open(&path).unwrap();
+    for query in ["project", "plumber", "electrican", "groceries", "managing review"] {
+        let search = Search::fuzzy(query);
+        assert_eq!(ids(&list.find(&search)), ids(&search.run(list.todos())), "{}", query);
+    }
+    assert_eq!(ids(&list.find(&Search::fuzzy("electrican"))), [1]);
+    assert!(list.find(&Search::fuzzy("plumber")).is_empty());
+}
+
+#[test]
+fn reports_match_spans_per_field() {
+    let todos = todos();
+    let hits = Search::fuzzy("review project").run(&todos);
+    assert_eq!(hits[0].spans_in(Field::Title), [(0, 7), (19, 25)]);
+
+    let hits = Search::fuzzy("budget").run(&todos);
+    assert_eq!(hits[0].spans_in(Field::Notes), [(22, 28)]);
+    assert!(hits[0].spans_in(Field::Title).is_empty());
+}
+
+#[test]
+fn regex_mode_matches_spans_and_rejects_bad_patterns() {
+    let todos = todos();
+    let hits = Search::regex(r"gro\w+").unwrap().run(&todos);
+    assert_eq!(ids(&hits), [2]);
+    assert_eq!(hits[0].spans_in(Field::Title), [(4, 13)]);
+    assert_eq!(ids(&Search::regex("(?-i)project").unwrap().run(&todos)), [1, 2]);
+    assert!(Search::regex("(").is_err());
+}
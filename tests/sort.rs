@@ -0,0 +1,121 @@
+use chrono::{Local, NaiveDate, TimeZone};
+use todo_list::{group_todos, local_due, sort_todos, DueBucket, GroupBy, SortField, SortKey, Status, Todo};
+
+// A Wednesday.
+fn today() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 11, 27).unwrap()
+}
+
+fn todo(id: usize, title: &str, priority: &str, due: Option<(u32, u32)>) -> Todo {
+    let mut todo = Todo::new(title.to_string(), Some(priority.to_string()), None, vec![]).unwrap();
+    todo.id = id;
+    todo.due_date = due.map(|(month, day)| {
+        local_due(NaiveDate::from_ymd_opt(2024, month, day).unwrap().and_hms_opt(12, 0, 0).unwrap())
+    });
+    todo
+}
+
+fn sorted(todos: &[Todo], spec: &str) -> Vec<usize> {
+    let mut refs: Vec<&Todo> = todos.iter().collect();
+    sort_todos(&mut refs, &SortKey::parse_list(spec).unwrap());
+    refs.iter().map(|todo| todo.id).collect()
+}
+
+#[test]
+fn parses_sort_keys() {
+    let keys = SortKey::parse_list("priority, -due,title:desc,-created:desc").unwrap();
+    assert_eq!(
+        keys,
+        [
+            SortKey { field: SortField::Priority, descending: false },
+            SortKey { field: SortField::Due, descending: true },
+            SortKey { field: SortField::Title, descending: true },
+            SortKey { field: SortField::Created, descending: false },
+        ]
+    );
+    assert!(SortKey::parse_list("urgency").is_err());
+    assert!(SortKey::parse_list("due:sideways").is_err());
+}
+
+#[test]
+fn sorts_by_each_key_in_turn() {
+    let todos = [
+        todo(1, "b", "low", Some((12, 1))),
+        todo(2, "a", "high", Some((12, 3))),
+        todo(3, "C", "high", Some((12, 1))),
+        todo(4, "c", "low", Some((12, 1))),
+    ];
+    assert_eq!(sorted(&todos, "priority,due"), [3, 2, 1, 4]);
+    assert_eq!(sorted(&todos, "priority,-due"), [2, 3, 1, 4]);
+    assert_eq!(sorted(&todos, "-priority,title"), [1, 4, 2, 3]);
+    // Titles compare case-insensitively; ties keep list order.
+    assert_eq!(sorted(&todos, "title:desc"), [3, 4, 1, 2]);
+    assert_eq!(sorted(&todos, "due"), [1, 3, 4, 2]);
+    assert_eq!(sorted(&todos, "-id"), [4, 3, 2, 1]);
+}
+
+#[test]
+fn todos_missing_the_field_sort_last_either_way() {
+    let mut todos = [todo(1, "a", "low", None), todo(2, "b", "low", Some((12, 5))), todo(3, "c", "low", None), todo(4, "d", "low", Some((12, 2)))];
+    assert_eq!(sorted(&todos, "due"), [4, 2, 1, 3]);
+    assert_eq!(sorted(&todos, "-due"), [2, 4, 1, 3]);
+
+    for (index, day) in [(1, 21), (2, 20)] {
+        todos[index].set_status(Status::Done);
+        todos[index].completed_at = Some(Local.with_ymd_and_hms(2024, 11, day, 9, 0, 0).unwrap());
+    }
+    assert_eq!(sorted(&todos, "completed"), [3, 2, 1, 4]);
+    assert_eq!(sorted(&todos, "completed:desc"), [2, 3, 1, 4]);
+}
+
+#[test]
+fn groups_by_due_bucket() {
+    let todos = [
+        todo(1, "later", "low", Some((12, 2))),
+        todo(2, "none", "low", None),
+        todo(3, "sunday", "low", Some((12, 1))),
+        todo(4, "overdue", "low", Some((11, 26))),
+        todo(5, "today", "low", Some((11, 27))),
+        todo(6, "thursday", "low", Some((11, 28))),
+    ];
+    let buckets: Vec<DueBucket> = todos.iter().map(|todo| DueBucket::of(todo, today())).collect();
+    assert_eq!(
+        buckets,
+        [DueBucket::Later, DueBucket::NoDueDate, DueBucket::ThisWeek, DueBucket::Overdue, DueBucket::Today, DueBucket::ThisWeek]
+    );
+
+    let refs: Vec<&Todo> = todos.iter().collect();
+    let groups: Vec<(String, Vec<usize>)> = group_todos(&refs, GroupBy::Due, today())
+        .into_iter()
+        .map(|group| (group.title, group.todos.iter().map(|todo| todo.id).collect()))
+        .collect();
+    let expected = [("Overdue", vec![4]), ("Today", vec![5]), ("This week", vec![3, 6]), ("Later", vec![1]), ("No due date", vec![2])];
+    assert_eq!(groups, expected.map(|(title, ids)| (title.to_string(), ids)));
+
+    // On a Sunday the week ends today, so tomorrow is already later.
+    let sunday = NaiveDate::from_ymd_opt(2024, 12, 1).unwrap();
+    assert_eq!(DueBucket::of(&todos[2], sunday), DueBucket::Today);
+    assert_eq!(DueBucket::of(&todos[0], sunday), DueBucket::Later);
+}
+
+#[test]
+fn groups_in_a_fixed_order_and_skips_empty_groups() {
+    let mut todos = [todo(1, "a", "low", None), todo(2, "b", "high", None), todo(3, "c", "low", None)];
+    todos[0].categories = vec!["work".into(), "home".into()];
+    todos[1].categories = vec!["work".into()];
+    todos[2].set_status(Status::Blocked);
+    let refs: Vec<&Todo> = todos.iter().collect();
+    let titles = |by| -> Vec<(String, Vec<usize>)> {
+        group_todos(&refs, by, today())
+            .into_iter()
+            .map(|group| (group.title, group.todos.iter().map(|todo| todo.id).collect()))
+            .collect()
+    };
+
+    assert_eq!(titles(GroupBy::Priority), [("high".to_string(), vec![2]), ("low".to_string(), vec![1, 3])]);
+    assert_eq!(titles(GroupBy::Status), [("todo".to_string(), vec![1, 2]), ("blocked".to_string(), vec![3])]);
+    assert_eq!(
+        titles(GroupBy::Tag),
+        [("home".to_string(), vec![1]), ("work".to_string(), vec![1, 2]), ("(untagged)".to_string(), vec![3])]
+    );
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use std::collections::HashSet;
+use std::fs;
+
+use common::TempDir;
+use serde_json::Value;
+use todo_list::TodoList;
+
+#[test]
+fn every_todo_gets_its_own_uuid() {
+    let dir = TempDir::new("storage");
+    let mut list = TodoList::open(dir.data_file()).unwrap();
+    for title in ["a", "b", "c"] {
+        list.add(title.into(), None, None, vec![]).unwrap();
+    }
+    let uuids: HashSet<_> = list.todos().iter().map(|todo| todo.uuid.unwrap()).collect();
+    assert_eq!(uuids.len(), 3);
+
+    let list = TodoList::open(dir.data_file()).unwrap();
+    let second = list.get(2).unwrap();
+    assert_eq!(list.get_by_uuid(second.uuid.unwrap()).unwrap().title, "b");
+}
+
+#[test]
+fn ids_of_deleted_todos_are_never_reused() {
+    let dir = TempDir::new("storage");
+    let mut list = TodoList::open(dir.data_file()).unwrap();
+    for title in ["a", "b", "c"] {
+        list.add(title.into(), None, None, vec![]).unwrap();
+    }
+    list.delete(3).unwrap();
+    list.delete(2).unwrap();
+    assert_eq!(list.add("d".into(), None, None, vec![]).unwrap().id, 4);
+
+    let mut list = TodoList::open(dir.data_file()).unwrap();
+    list.delete(4).unwrap();
+    let mut list = TodoList::open(dir.data_file()).unwrap();
+    assert_eq!(list.add("e".into(), None, None, vec![]).unwrap().id, 5);
+
+    let saved: Value = serde_json::from_str(&fs::read_to_string(dir.data_file()).unwrap()).unwrap();
+    assert_eq!(saved["next_id"], 6);
+}
+
+#[test]
+fn saves_replace_the_file_in_one_step() {
+    let dir = TempDir::new("storage");
+    let path = dir.path().join("nested").join("todos.json");
+    let mut list = TodoList::open(&path).unwrap();
+    list.add("a".into(), None, None, vec![]).unwrap();
+    let before = fs::read_to_string(&path).unwrap();
+
+    // With the temporary file blocked, the save fails and the old file stays whole.
+    let tmp = path.with_file_name(".todos.json.tmp");
+    fs::create_dir(&tmp).unwrap();
+    assert!(list.add("b".into(), None, None, vec![]).is_err());
+    assert_eq!(fs::read_to_string(&path).unwrap(), before);
+
+    fs::remove_dir(&tmp).unwrap();
+    list.save().unwrap();
+    assert!(!tmp.exists());
+    let titles: Vec<String> = TodoList::open(&path).unwrap().todos().iter().map(|todo| todo.title.clone()).collect();
+    assert_eq!(titles, ["a", "b"]);
+}
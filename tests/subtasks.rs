@@ -0,0 +1,86 @@
+mod common;
+
+use common::TempDir;
+use todo_list::{tree, ChildPolicy, Error, Progress, Status, Todo, TodoEdit, TodoList};
+
+/// A list with #1 Launch > #2 Docs > #3 API docs, and #4 Ship under #1.
+fn project() -> (TempDir, TodoList) {
+    let dir = TempDir::new("subtasks");
+    let mut list = TodoList::open(dir.data_file()).unwrap();
+    for (title, parent) in [("Launch", None), ("Docs", Some(1)), ("API docs", Some(2)), ("Ship", Some(1))] {
+        let mut todo = Todo::new(title.into(), None, None, vec![]).unwrap();
+        todo.parent_id = parent;
+        list.add_todo(todo).unwrap();
+    }
+    (dir, list)
+}
+
+fn ids(list: &TodoList) -> Vec<usize> {
+    list.todos().iter().map(|todo| todo.id).collect()
+}
+
+#[test]
+fn renders_depth_first_under_parents() {
+    let (_dir, list) = project();
+    let mut todos: Vec<&Todo> = list.todos().iter().collect();
+    todos.reverse();
+    let nodes: Vec<(usize, usize)> = tree(&todos).iter().map(|node| (node.todo.id, node.depth)).collect();
+    assert_eq!(nodes, [(1, 0), (4, 1), (2, 1), (3, 2)]);
+
+    // Without its parent a subtask starts its own tree.
+    let nodes: Vec<(usize, usize)> = tree(&todos[..2]).iter().map(|node| (node.todo.id, node.depth)).collect();
+    assert_eq!(nodes, [(4, 0), (3, 0)]);
+}
+
+#[test]
+fn rolls_up_progress_of_direct_subtasks() {
+    let (_dir, mut list) = project();
+    assert_eq!(list.progress(1), Some(Progress { done: 0, total: 2 }));
+    list.complete(4).unwrap();
+    assert_eq!(list.progress(1), Some(Progress { done: 1, total: 2 }));
+    assert_eq!(list.progress(3), None);
+
+    list.cancel(3).unwrap();
+    let overview = list.overview();
+    for todo in list.todos() {
+        assert_eq!(overview.progress(todo.id), list.progress(todo.id), "#{}", todo.id);
+    }
+}
+
+#[test]
+fn closing_a_parent_follows_the_child_policy() {
+    let (_dir, mut list) = project();
+    assert!(matches!(list.complete(1), Err(Error::HasSubtasks(1, children)) if children == [2, 4, 3]));
+    assert!(list.todos().iter().all(|todo| todo.status == Status::Todo));
+
+    let changed = list.set_status_many(&[2], Status::Done, ChildPolicy::Cascade).unwrap();
+    assert_eq!(changed, [2, 3]);
+
+    list.set_status_many(&[1], Status::Cancelled, ChildPolicy::Orphan).unwrap();
+    assert_eq!(list.get(4).unwrap().parent_id, None);
+    assert_eq!(list.get(2).unwrap().parent_id, Some(1));
+}
+
+#[test]
+fn deleting_a_parent_follows_the_child_policy() {
+    let (_dir, mut list) = project();
+    assert!(matches!(list.delete(2), Err(Error::HasSubtasks(2, _))));
+
+    list.delete_many(&[2], ChildPolicy::Orphan).unwrap();
+    assert_eq!(list.get(3).unwrap().parent_id, None);
+
+    list.delete_many(&[1], ChildPolicy::Cascade).unwrap();
+    assert_eq!(ids(&list), [3]);
+    list.undo().unwrap();
+    assert_eq!(ids(&list), [1, 3, 4]);
+}
+
+#[test]
+fn refuses_parent_cycles() {
+    let (_dir, mut list) = project();
+    let move_under = |parent| TodoEdit { parent_id: Some(Some(parent)), ..TodoEdit::default() };
+    assert!(matches!(list.edit(1, move_under(3)), Err(Error::Invalid(_))));
+    assert!(matches!(list.edit(2, move_under(2)), Err(Error::Invalid(_))));
+    assert!(matches!(list.edit(2, move_under(9)), Err(Error::NotFound(9))));
+    assert_eq!(list.edit(3, move_under(4)).unwrap().parent_id, Some(4));
+}
@@ -0,0 +1,104 @@
+use std::ffi::OsString;
+
+use colored::{Color, Colorize};
+use todo_list::theme::{ColorChoice, Style, Symbols, BUNDLED};
+use todo_list::{Config, Theme};
+
+#[test]
+fn bundled_themes_load() {
+    for name in BUNDLED {
+        let theme = Theme::bundled(name).unwrap();
+        assert!(theme.shows("due"), "{}", name);
+    }
+    assert!(Theme::bundled("dark").is_none());
+    assert_eq!(Theme::bundled("monochrome").unwrap().symbols, Theme::default().symbols);
+    assert_ne!(Theme::bundled("monochrome").unwrap().colors, Theme::default().colors);
+    assert_eq!(Symbols::ascii().done, "[x]");
+}
+
+#[test]
+fn config_overrides_single_entries() {
+    let theme = Theme::from_config(
+        r#"
+        [theme]
+        base = "high-contrast"
+        fields = ["priority", "due"]
+
+        [theme.colors]
+        accent = "magenta"
+
+        [theme.symbols]
+        done = "✔"
+        "#,
+    )
+    .unwrap();
+    let base = Theme::bundled("high-contrast").unwrap();
+    assert_eq!(theme.colors.accent, Style::parse("magenta").unwrap());
+    assert_eq!(theme.colors.error, base.colors.error);
+    assert_eq!(theme.symbols.done, "✔");
+    assert_eq!(theme.symbols.todo, base.symbols.todo);
+    assert!(theme.shows("due") && !theme.shows("created"));
+
+    // Settings outside `[theme]` are the config's business.
+    assert_eq!(Theme::from_config("backup_limit = 3").unwrap(), Theme::default());
+    assert_eq!(Config::parse("backup_limit = 3").unwrap(), Config { theme: Theme::default(), backup_limit: 3 });
+}
+
+#[test]
+fn rejects_unknown_names_and_styles() {
+    for config in [
+        "[theme]\nbase = \"dark\"",
+        "[theme.colors]\nacent = \"red\"",
+        "[theme.colors]\naccent = \"redd\"",
+        "[theme.symbols]\nstar = \"*\"",
+        "[theme]\nfields = [\"due\", \"priorty\"]",
+        "[theme",
+    ] {
+        assert!(Theme::from_config(config).is_err(), "{}", config);
+    }
+}
+
+#[test]
+fn parses_styles() {
+    colored::control::set_override(true);
+    let style = Style::parse("Bold bright white on #ff8800").unwrap();
+    assert_eq!(
+        style.paint("x").to_string(),
+        "x".color(Color::BrightWhite).on_color(Color::TrueColor { r: 255, g: 136, b: 0 }).bold().to_string()
+    );
+    assert_eq!(Style::parse("plain").unwrap().paint("x").to_string(), "x");
+    assert!(Style::parse("black on").is_err());
+    assert!(Style::parse("blinking").is_err());
+}
+
+/// Whether `choice` colors output with only `vars` set.
+fn colored(choice: ColorChoice, vars: &[(&str, &str)], is_terminal: bool) -> bool {
+    let var = |name: &str| vars.iter().find(|(key, _)| *key == name).map(|(_, value)| OsString::from(value));
+    choice.enabled_with(var, is_terminal)
+}
+
+#[test]
+fn color_choice_priority() {
+    assert!(matches!(ColorChoice::from_name("always"), Ok(ColorChoice::Always)));
+    assert!(ColorChoice::from_name("yes").is_err());
+
+    // --color=always|never beats the environment and the terminal.
+    let both = [("NO_COLOR", "1"), ("CLICOLOR_FORCE", "1")];
+    for is_terminal in [false, true] {
+        assert!(colored(ColorChoice::Always, &both, is_terminal));
+        assert!(!colored(ColorChoice::Never, &both, is_terminal));
+    }
+
+    // Then NO_COLOR, then CLICOLOR_FORCE, then the terminal.
+    assert!(!colored(ColorChoice::Auto, &both, true));
+    assert!(!colored(ColorChoice::Auto, &[("NO_COLOR", "1")], true));
+    assert!(colored(ColorChoice::Auto, &[("CLICOLOR_FORCE", "1")], false));
+    assert!(colored(ColorChoice::Auto, &[], true));
+    assert!(!colored(ColorChoice::Auto, &[], false));
+
+    // Empty values count as unset, and CLICOLOR_FORCE=0 does not force.
+    assert!(colored(ColorChoice::Auto, &[("NO_COLOR", ""), ("CLICOLOR_FORCE", "1")], false));
+    assert!(!colored(ColorChoice::Auto, &[("CLICOLOR_FORCE", "0")], false));
+    assert!(colored(ColorChoice::Auto, &[("CLICOLOR_FORCE", "0")], true));
+    assert!(!colored(ColorChoice::Auto, &[("CLICOLOR_FORCE", "")], false));
+}